@@ -0,0 +1,268 @@
+//! Avatar perceptual hashing and same-owner clustering (`--avatars`)
+//!
+//! A shared username doesn't prove a shared owner, but the same profile picture on two sites is
+//! a strong hint. Sites rescale and recompress uploads, so avatars are compared by perceptual
+//! hash: visually identical images hash to nearby values even when their bytes differ.
+
+use crate::checker::{CheckResult, SiteResult};
+use serde::Serialize;
+
+/// Default maximum Hamming distance, out of 64 bits, for two avatars to count as the same picture
+pub const DEFAULT_THRESHOLD: u32 = 10;
+
+/// Avatars larger than this are not downloaded
+pub const MAX_AVATAR_BYTES: usize = 5 * 1024 * 1024;
+
+/// 64-bit difference hash of an image (PNG, JPEG, GIF or WebP). The image is shrunk to 9x8
+/// grayscale pixels, and each bit records whether a pixel is brighter than its right neighbour.
+pub fn dhash(bytes: &[u8]) -> Result<u64, String> {
+    let image = image::load_from_memory(bytes).map_err(|e| format!("cannot decode avatar: {}", e))?;
+    let small = image.resize_exact(9, 8, image::imageops::FilterType::Triangle).to_luma8();
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let brighter = small.get_pixel(x, y)[0] > small.get_pixel(x + 1, y)[0];
+            hash = (hash << 1) | u64::from(brighter);
+        }
+    }
+    // Flat images (blank or single-colour placeholders) all hash alike and would match each other
+    if hash == 0 || hash == u64::MAX {
+        return Err("avatar is a flat placeholder image".to_string());
+    }
+    Ok(hash)
+}
+
+/// Number of differing bits between two hashes
+pub fn distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+pub fn format_hash(hash: u64) -> String {
+    format!("{:016x}", hash)
+}
+
+pub fn parse_hash(hex: &str) -> Option<u64> {
+    u64::from_str_radix(hex, 16).ok()
+}
+
+/// A found account in an avatar cluster
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterMember {
+    pub username: String,
+    pub site: String,
+    pub url: String,
+    pub avatar_url: String,
+}
+
+/// Accounts whose avatars match: likely the same owner
+#[derive(Debug, Clone, Serialize)]
+pub struct AvatarCluster {
+    pub members: Vec<ClusterMember>,
+    /// Largest Hamming distance between any two avatars in the cluster; 0 means identical pictures
+    pub max_distance: u32,
+}
+
+/// Group found accounts whose avatar hashes are within `threshold` bits of each other.
+/// Matching is transitive: if A matches B and B matches C, all three share a cluster.
+/// Only clusters of two or more accounts are returned, largest first.
+pub fn clusters(results: &[&SiteResult], threshold: u32) -> Vec<AvatarCluster> {
+    let hashed: Vec<(&SiteResult, &str, u64)> = results
+        .iter()
+        .filter(|r| r.result == CheckResult::Found)
+        .filter_map(|r| {
+            let profile = r.profile.as_ref()?;
+            let hash = parse_hash(profile.avatar_hash.as_deref()?)?;
+            Some((*r, profile.avatar_url.as_deref()?, hash))
+        })
+        .collect();
+
+    // Union-find over the accounts
+    let mut parent: Vec<usize> = (0..hashed.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for i in 0..hashed.len() {
+        for j in i + 1..hashed.len() {
+            if distance(hashed[i].2, hashed[j].2) <= threshold {
+                let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                parent[b] = a;
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_of_root: Vec<Option<usize>> = vec![None; hashed.len()];
+    for i in 0..hashed.len() {
+        let r = root(&mut parent, i);
+        match group_of_root[r] {
+            Some(group) => groups[group].push(i),
+            None => {
+                group_of_root[r] = Some(groups.len());
+                groups.push(vec![i]);
+            }
+        }
+    }
+
+    let mut clusters: Vec<AvatarCluster> = groups
+        .into_iter()
+        .filter(|group| group.len() > 1)
+        .map(|group| {
+            let max_distance = group
+                .iter()
+                .flat_map(|&i| group.iter().map(move |&j| (i, j)))
+                .map(|(i, j)| distance(hashed[i].2, hashed[j].2))
+                .max()
+                .unwrap_or(0);
+            let members = group
+                .iter()
+                .map(|&i| {
+                    let (result, avatar_url, _) = hashed[i];
+                    ClusterMember {
+                        username: result.username.clone(),
+                        site: result.site.clone(),
+                        url: result.url.clone(),
+                        avatar_url: avatar_url.to_string(),
+                    }
+                })
+                .collect();
+            AvatarCluster { members, max_distance }
+        })
+        .collect();
+    clusters.sort_by(|a, b| b.members.len().cmp(&a.members.len()).then(a.max_distance.cmp(&b.max_distance)));
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::Profile;
+    use image::{DynamicImage, ImageFormat, Rgb, RgbImage};
+    use std::io::Cursor;
+
+    /// A 256x256 picture with a gradient, a disc and a bar, so its hash has structure
+    fn picture() -> RgbImage {
+        RgbImage::from_fn(256, 256, |x, y| {
+            let (dx, dy) = (x as i64 - 96, y as i64 - 128);
+            if dx * dx + dy * dy < 60 * 60 {
+                Rgb([230, 200, 40])
+            } else if (170..200).contains(&x) && y > 40 {
+                Rgb([20, 40, 160])
+            } else {
+                Rgb([(x / 2) as u8, (y / 2) as u8, ((x + y) / 4) as u8])
+            }
+        })
+    }
+
+    fn png(image: &RgbImage) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png).unwrap();
+        bytes
+    }
+
+    fn jpeg(image: &RgbImage, quality: u8) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image::codecs::jpeg::JpegEncoder::new_with_quality(&mut bytes, quality).encode_image(image).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn rescaled_and_recompressed_copies_stay_close() {
+        let original = picture();
+        let hash = dhash(&png(&original)).unwrap();
+        let small = DynamicImage::ImageRgb8(original.clone()).resize_exact(73, 73, image::imageops::FilterType::Lanczos3).to_rgb8();
+        for (copy, bytes) in [("jpeg", jpeg(&original, 35)), ("rescaled png", png(&small)), ("rescaled jpeg", jpeg(&small, 60))] {
+            let bits = distance(hash, dhash(&bytes).unwrap());
+            assert!(bits <= DEFAULT_THRESHOLD, "{} is {} bits away", copy, bits);
+        }
+        // The same picture mirrored is a different avatar
+        let other = dhash(&png(&image::imageops::flip_horizontal(&original))).unwrap();
+        assert!(distance(hash, other) > 2 * DEFAULT_THRESHOLD, "a different picture is only {} bits away", distance(hash, other));
+    }
+
+    #[test]
+    fn flat_placeholders_are_rejected() {
+        for colour in [[255, 255, 255], [0, 0, 0], [90, 120, 200]] {
+            let flat = RgbImage::from_pixel(128, 128, Rgb(colour));
+            assert_eq!(dhash(&png(&flat)), Err("avatar is a flat placeholder image".to_string()));
+        }
+        assert!(dhash(b"<html>not an image</html>").unwrap_err().starts_with("cannot decode avatar"));
+    }
+
+    #[test]
+    fn hashes_round_trip_as_hex() {
+        assert_eq!(format_hash(0xbeef), "000000000000beef");
+        assert_eq!(parse_hash(&format_hash(0x0123_4567_89ab_cdef)), Some(0x0123_4567_89ab_cdef));
+        assert_eq!(parse_hash("not hex"), None);
+    }
+
+    fn account(site: &str, hash: Option<u64>) -> SiteResult {
+        SiteResult {
+            username: "alice".to_string(),
+            seed: None,
+            site: site.to_string(),
+            url: format!("https://{}.example/alice", site),
+            category: "Social".to_string(),
+            result: CheckResult::Found,
+            status_code: Some(200),
+            final_url: None,
+            attempts: 1,
+            confidence: 0.9,
+            evidence: Vec::new(),
+            profile: Some(Profile {
+                avatar_url: Some(format!("https://{}.example/alice.png", site)),
+                avatar_hash: hash.map(format_hash),
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn sites(cluster: &AvatarCluster) -> Vec<&str> {
+        let mut sites: Vec<&str> = cluster.members.iter().map(|m| m.site.as_str()).collect();
+        sites.sort();
+        sites
+    }
+
+    #[test]
+    fn clustering_is_transitive() {
+        let a = 0xf0f0_f0f0_f0f0_f0f0u64;
+        let b = a ^ 0x3f; // 6 bits from a
+        let c = b ^ 0x3f00; // 6 bits from b, 12 from a
+        let results = [account("a", Some(a)), account("c", Some(c)), account("b", Some(b))];
+        let found = clusters(&results.iter().collect::<Vec<_>>(), DEFAULT_THRESHOLD);
+        assert_eq!(found.len(), 1);
+        assert_eq!(sites(&found[0]), ["a", "b", "c"]);
+        assert_eq!(found[0].max_distance, 12);
+
+        // Without b in between, a and c are too far apart
+        let results = [account("a", Some(a)), account("c", Some(c))];
+        assert!(clusters(&results.iter().collect::<Vec<_>>(), DEFAULT_THRESHOLD).is_empty());
+    }
+
+    #[test]
+    fn clusters_are_largest_first_then_closest() {
+        let (x, y, z) = (0x00ff_00ff_00ff_00ffu64, 0x0f0f_0f0f_0f0f_0f0fu64, 0x3333_3333_3333_3333u64);
+        let mut unfound = account("unfound", Some(z));
+        unfound.result = CheckResult::NotFound;
+        let results = [
+            account("y1", Some(y)),
+            account("y2", Some(y ^ 0b11)),
+            account("z1", Some(z)),
+            account("z2", Some(z)),
+            account("x1", Some(x)),
+            account("x2", Some(x ^ 1)),
+            account("x3", Some(x ^ 0b10)),
+            account("loner", Some(!x)),
+            account("unhashed", None),
+            unfound,
+        ];
+        let found = clusters(&results.iter().collect::<Vec<_>>(), DEFAULT_THRESHOLD);
+        let summary: Vec<(Vec<&str>, u32)> = found.iter().map(|c| (sites(c), c.max_distance)).collect();
+        assert_eq!(summary, [(vec!["x1", "x2", "x3"], 2), (vec!["z1", "z2"], 0), (vec!["y1", "y2"], 2)]);
+        assert_eq!(found[1].members[0].avatar_url, "https://z1.example/alice.png");
+    }
+}
@@ -1,44 +1,557 @@
-use crate::sites::Site;
-use reqwest::Client;
+use crate::avatar;
+use crate::fixture::{self, Fixture, FixtureMode, RecordedResponse, Redirect};
+use crate::profile::{self, Profile};
+use crate::scheduler::{host_of, HostLimits, HostPermit, HostScheduler};
+use crate::sites::{Site, UsernameLocation};
+use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Outcome of checking one site. Serialized with a stable snake_case `status` tag
+/// and, for variants that carry one, a `detail` payload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", content = "detail", rename_all = "snake_case")]
 pub enum CheckResult {
     Found,
     NotFound,
+    /// HTTP 429 or equivalent; `retry_after_secs` comes from the Retry-After header when present
+    RateLimited { retry_after_secs: Option<u64> },
+    /// Anti-bot challenge page (Cloudflare, LinkedIn's HTTP 999, captchas)
+    Challenge(String),
+    /// The site refuses to answer without authentication
+    Blocked(String),
+    /// The site's hostname could not be resolved
+    DnsError(String),
+    /// TLS handshake or certificate failure
+    TlsError(String),
+    /// The site rejected the username itself (bad characters, length, format)
+    InvalidUsername(String),
+    /// The site cannot be checked by username at all
+    Unsupported(String),
+    /// Connection failure or server-side error (5xx): the site may answer if asked again later
+    Unavailable(String),
+    /// Any other failure, such as an unexpected HTTP status or an answer the site's rules don't recognise
     Error(String),
     Timeout,
 }
 
+impl CheckResult {
+    /// Stable machine-readable name, matching the serialized `status` tag
+    pub fn tag(&self) -> &'static str {
+        match self {
+            CheckResult::Found => "found",
+            CheckResult::NotFound => "not_found",
+            CheckResult::RateLimited { .. } => "rate_limited",
+            CheckResult::Challenge(_) => "challenge",
+            CheckResult::Blocked(_) => "blocked",
+            CheckResult::DnsError(_) => "dns_error",
+            CheckResult::TlsError(_) => "tls_error",
+            CheckResult::InvalidUsername(_) => "invalid_username",
+            CheckResult::Unsupported(_) => "unsupported",
+            CheckResult::Unavailable(_) => "unavailable",
+            CheckResult::Error(_) => "error",
+            CheckResult::Timeout => "timeout",
+        }
+    }
+
+    /// Human-readable name of the variant
+    pub fn label(&self) -> &'static str {
+        match self {
+            CheckResult::Found => "Found",
+            CheckResult::NotFound => "Not Found",
+            CheckResult::RateLimited { .. } => "Rate Limited",
+            CheckResult::Challenge(_) => "Challenge",
+            CheckResult::Blocked(_) => "Blocked",
+            CheckResult::DnsError(_) => "DNS Error",
+            CheckResult::TlsError(_) => "TLS Error",
+            CheckResult::InvalidUsername(_) => "Invalid Username",
+            CheckResult::Unsupported(_) => "Unsupported",
+            CheckResult::Unavailable(_) => "Unavailable",
+            CheckResult::Error(_) => "Error",
+            CheckResult::Timeout => "Timeout",
+        }
+    }
+
+    /// Whether the check reached a verdict about the account
+    pub fn is_conclusive(&self) -> bool {
+        matches!(self, CheckResult::Found | CheckResult::NotFound)
+    }
+
+    /// Whether trying again later might give a different answer
+    pub fn is_transient(&self) -> bool {
+        matches!(self, CheckResult::Timeout | CheckResult::RateLimited { .. } | CheckResult::Unavailable(_))
+    }
+}
+
+impl fmt::Display for CheckResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckResult::RateLimited { retry_after_secs: Some(secs) } => write!(f, "Rate limited (retry after {}s)", secs),
+            CheckResult::RateLimited { retry_after_secs: None } => write!(f, "Rate limited (try again later)"),
+            CheckResult::Challenge(detail) |
+            CheckResult::Blocked(detail) |
+            CheckResult::DnsError(detail) |
+            CheckResult::TlsError(detail) |
+            CheckResult::InvalidUsername(detail) |
+            CheckResult::Unsupported(detail) |
+            CheckResult::Unavailable(detail) |
+            CheckResult::Error(detail) => write!(f, "{}: {}", self.label(), detail),
+            _ => write!(f, "{}", self.label()),
+        }
+    }
+}
+
+/// Parse a Retry-After header, either delay-seconds or an HTTP date
+fn parse_retry_after(value: Option<&str>) -> Option<u64> {
+    let value = value?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(secs);
+    }
+    let when = httpdate::parse_http_date(value).ok()?;
+    Some(when.duration_since(SystemTime::now()).map(|d| d.as_secs()).unwrap_or(0))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SiteResult {
+    /// The username that was checked
+    pub username: String,
+    /// The input username this one was derived from, when it is a `--permute` variant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<String>,
     pub site: String,
     pub url: String,
     pub category: String,
     pub result: CheckResult,
+    /// HTTP status of the last response, when the site answered at all
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    /// URL of the last response after redirects
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_url: Option<String>,
+    /// Number of requests made, including retries; 0 when the username was rejected up front
+    pub attempts: u32,
+    /// How sure we are of `result`, from 0.0 (no idea) to 1.0 (certain)
+    pub confidence: f32,
+    /// The signals that led to `result`, in the order they fired
+    pub evidence: Vec<Evidence>,
+    /// Metadata read from the profile page, for found accounts when `extract_profiles` is on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<Profile>,
+}
+
+/// Which stage of detection produced a piece of evidence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvidenceSource {
+    /// HTTP status code
+    Status,
+    /// HTTP redirects and the final URL
+    Redirect,
+    /// JavaScript `location` redirects in the body
+    JsRedirect,
+    /// `<meta http-equiv="refresh">` redirects
+    MetaRefresh,
+    /// The site's declarative rules
+    SiteRules,
+    /// Generic "not found" wording and 404 markers in the body
+    NotFoundMessage,
+    /// Single page app shell heuristics
+    SpaShell,
+    /// A site API response
+    Api,
+    /// Network-level outcome (timeouts, DNS, TLS)
+    Network,
+    /// A failed attempt that was retried
+    Retry,
+}
+
+/// Which outcome a piece of evidence points to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Indication {
+    Found,
+    NotFound,
+    Inconclusive,
+}
+
+/// One signal that fired while checking a site
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Evidence {
+    pub source: EvidenceSource,
+    pub indicates: Indication,
+    /// How strong the signal is, from 0.0 to 1.0
+    pub weight: f32,
+    pub detail: String,
+}
+
+impl Evidence {
+    pub fn found(source: EvidenceSource, weight: f32, detail: impl Into<String>) -> Self {
+        Self { source, indicates: Indication::Found, weight, detail: detail.into() }
+    }
+
+    pub fn not_found(source: EvidenceSource, weight: f32, detail: impl Into<String>) -> Self {
+        Self { source, indicates: Indication::NotFound, weight, detail: detail.into() }
+    }
+
+    pub fn inconclusive(source: EvidenceSource, detail: impl Into<String>) -> Self {
+        Self { source, indicates: Indication::Inconclusive, weight: 0.0, detail: detail.into() }
+    }
+}
+
+/// Combine the evidence into a confidence value for `result`.
+/// Agreeing signals are treated as independent, each removing part of the remaining doubt;
+/// signals pointing the other way pull the score down. Errors always score 0.0.
+pub fn confidence_for(result: &CheckResult, evidence: &[Evidence]) -> f32 {
+    let (agrees, disagrees) = match result {
+        CheckResult::Found => (Indication::Found, Indication::NotFound),
+        CheckResult::NotFound => (Indication::NotFound, Indication::Found),
+        _ => return 0.0,
+    };
+    let combine = |indication: Indication| -> f32 {
+        let doubt: f32 = evidence.iter()
+            .filter(|e| e.indicates == indication)
+            .map(|e| 1.0 - e.weight.clamp(0.0, 1.0))
+            .product();
+        1.0 - doubt
+    };
+    combine(agrees) * (1.0 - combine(disagrees) / 2.0)
+}
+
+/// Longest Retry-After we are willing to wait; anything longer is reported as RateLimited
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+/// Cap on the exponential backoff between attempts
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Tunables for an `AccountChecker`
+#[derive(Debug, Clone)]
+pub struct CheckerOptions {
+    /// How many times to retry a transient failure (timeouts, 429, 5xx, network errors)
+    pub retries: u32,
+    /// Base delay before the first retry; doubled for every further attempt
+    pub retry_delay: Duration,
+    /// Default requests in flight per host, for sites without a `rate_limit`
+    pub host_concurrency: usize,
+    /// Default minimum spacing between request starts per host
+    pub host_interval: Duration,
+    /// Proxies to route requests through (http://, https://, socks5:// or socks5h://); empty for direct
+    pub proxies: Vec<String>,
+    /// How requests are spread over `proxies`
+    pub proxy_mode: ProxyMode,
+    /// Extract profile metadata from the pages of found accounts
+    pub extract_profiles: bool,
+    /// Download the avatars of extracted profiles and store their perceptual hash
+    pub hash_avatars: bool,
+    /// Record responses to, or replay them from, a fixture directory
+    pub fixtures: Option<FixtureMode>,
+}
+
+impl Default for CheckerOptions {
+    fn default() -> Self {
+        Self {
+            retries: 2,
+            retry_delay: Duration::from_millis(500),
+            host_concurrency: 2,
+            host_interval: Duration::from_millis(250),
+            proxies: Vec::new(),
+            proxy_mode: ProxyMode::RoundRobin,
+            extract_profiles: false,
+            hash_avatars: false,
+            fixtures: None,
+        }
+    }
+}
+
+/// Proxy selection when several proxies are configured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyMode {
+    /// Each request (including retries) takes the next proxy in turn
+    RoundRobin,
+    /// Each site always uses the same proxy, picked from its name
+    PerSite,
+}
+
+impl std::str::FromStr for ProxyMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "round-robin" => Ok(ProxyMode::RoundRobin),
+            "per-site" => Ok(ProxyMode::PerSite),
+            _ => Err(format!("unknown proxy mode `{}` (expected round-robin or per-site)", s)),
+        }
+    }
+}
+
+/// The parts of the HTTP response kept on the `SiteResult`
+#[derive(Debug, Default)]
+struct ResponseMeta {
+    status_code: Option<u16>,
+    final_url: Option<String>,
+    /// The page body, kept only when extracting profiles
+    body: Option<String>,
+    /// The whole response, kept only when recording fixtures
+    recorded: Option<RecordedResponse>,
+}
+
+/// Redirects followed before giving up, as many as browsers and reqwest's default policy allow
+const MAX_REDIRECTS: usize = 10;
+
+/// One request a check sends
+struct ProbeRequest {
+    method: reqwest::Method,
+    url: String,
+    headers: Vec<(String, String)>,
+    json: Option<serde_json::Value>,
+    form: Vec<(String, String)>,
+}
+
+impl ProbeRequest {
+    /// The request a site's check sends for a username: a GET of its profile URL unless
+    /// `[site.request]` says otherwise
+    fn for_site(site: &Site, username: &str) -> Self {
+        let spec = &site.request;
+        let fill = |template: &String| template.replace("{}", username);
+        let method = spec.method.as_deref().unwrap_or("GET").to_ascii_uppercase();
+        let mut headers: Vec<(String, String)> = spec.headers.iter().map(|(name, value)| (name.clone(), fill(value))).collect();
+        if !spec.cookies.is_empty() {
+            let cookies: Vec<String> = spec.cookies.iter().map(|(name, value)| format!("{}={}", name, fill(value))).collect();
+            headers.push(("Cookie".to_string(), cookies.join("; ")));
+        }
+        Self {
+            // Catalogues are validated on load, so the method is known to parse
+            method: reqwest::Method::from_bytes(method.as_bytes()).unwrap_or(reqwest::Method::GET),
+            url: fill(spec.url.as_ref().unwrap_or(&site.url)),
+            headers,
+            json: spec.json.as_ref().map(|json| fill_json(json, username)),
+            form: spec.form.iter().map(|(name, value)| (name.clone(), fill(value))).collect(),
+        }
+    }
+
+    /// A bare GET, for requests that aren't a site's check (avatars)
+    fn get(url: &str) -> Self {
+        Self { method: reqwest::Method::GET, url: url.to_string(), headers: Vec::new(), json: None, form: Vec::new() }
+    }
+
+    /// This request, or one hop of its redirect chain: sent to `url` with `method`, carrying the
+    /// body only when `with_body` and the headers only when `with_headers`
+    fn builder(&self, client: &Client, url: &str, method: reqwest::Method, with_body: bool, with_headers: bool) -> reqwest::RequestBuilder {
+        let mut builder = client.request(method, url);
+        if with_headers {
+            for (name, value) in &self.headers {
+                builder = builder.header(name, value);
+            }
+        }
+        if with_body {
+            if let Some(json) = &self.json {
+                builder = builder.json(json);
+            } else if !self.form.is_empty() {
+                builder = builder.form(&self.form);
+            }
+        }
+        builder
+    }
+}
+
+/// A JSON body template with `{}` in its string values replaced by the username
+fn fill_json(template: &serde_json::Value, username: &str) -> serde_json::Value {
+    use serde_json::Value;
+    match template {
+        Value::String(s) => Value::String(s.replace("{}", username)),
+        Value::Array(items) => Value::Array(items.iter().map(|item| fill_json(item, username)).collect()),
+        Value::Object(map) => Value::Object(map.iter().map(|(key, value)| (key.clone(), fill_json(value, username))).collect()),
+        other => other.clone(),
+    }
+}
+
+enum FetchError {
+    Network(reqwest::Error),
+    /// Too many redirects, or no usable fixture in replay mode
+    Other(String),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Network(e) => write!(f, "{}", e),
+            FetchError::Other(message) => write!(f, "{}", message),
+        }
+    }
 }
 
 pub struct AccountChecker {
-    client: Client,
+    /// One client per proxy, or a single direct client
+    clients: Vec<Client>,
+    next_client: AtomicUsize,
+    options: CheckerOptions,
+    scheduler: HostScheduler,
 }
 
 impl AccountChecker {
     pub fn new() -> Self {
-        let client = Client::builder()
+        Self::with_options(CheckerOptions::default()).expect("Failed to create HTTP client")
+    }
+
+    /// Build a checker; fails if a proxy URL is malformed or uses an unsupported scheme
+    pub fn with_options(options: CheckerOptions) -> Result<Self, reqwest::Error> {
+        let clients = if options.proxies.is_empty() {
+            vec![Self::build_client(None)?]
+        } else {
+            options.proxies.iter()
+                .map(|proxy| Self::build_client(Some(proxy)))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        Ok(Self { clients, next_client: AtomicUsize::new(0), options, scheduler: HostScheduler::new() })
+    }
+
+    fn build_client(proxy: Option<&str>) -> Result<Client, reqwest::Error> {
+        let mut builder = Client::builder()
             .timeout(Duration::from_secs(10))
             .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36")
-            .redirect(reqwest::redirect::Policy::limited(5)) // Follow redirects but check final URL
-            .build()
-            .expect("Failed to create HTTP client");
+            .redirect(reqwest::redirect::Policy::none()); // `fetch` follows redirects itself to record the chain
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+        builder.build()
+    }
+
+    /// The client (and so the proxy) to use for the next request to `site_name`
+    fn client_for(&self, site_name: &str) -> &Client {
+        if self.clients.len() == 1 {
+            return &self.clients[0];
+        }
+        let index = match self.options.proxy_mode {
+            ProxyMode::RoundRobin => self.next_client.fetch_add(1, Ordering::Relaxed),
+            ProxyMode::PerSite => {
+                let mut hasher = DefaultHasher::new();
+                site_name.hash(&mut hasher);
+                hasher.finish() as usize
+            }
+        };
+        &self.clients[index % self.clients.len()]
+    }
+
+    /// Politeness limits for a site's host: its own `rate_limit`, falling back to the defaults
+    fn host_limits(&self, site: Option<&Site>) -> HostLimits {
+        let limit = site.and_then(|s| s.rate_limit).unwrap_or_default();
+        HostLimits {
+            concurrency: limit.concurrency.unwrap_or(self.options.host_concurrency),
+            interval: limit.interval_ms.map(Duration::from_millis).unwrap_or(self.options.host_interval),
+        }
+    }
+
+    /// Send a check's request and read the whole response. In replay mode the response comes
+    /// from the fixture directory instead, and the network is never touched.
+    async fn fetch(&self, site_name: &str, username: &str, limits: HostLimits, request: &ProbeRequest) -> Result<RecordedResponse, FetchError> {
+        if let Some(FixtureMode::Replay(dir)) = &self.options.fixtures {
+            return fixture::load_response(dir, site_name, username).map_err(FetchError::Other);
+        }
+
+        // The last host's politeness budget is held until the body has been read
+        let client = self.client_for(site_name);
+        let (response, redirects, _permit) = self.send_following(client, request, limits).await?;
+
+        let status = response.status().as_u16();
+        let final_url = response.url().to_string();
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(name, value)| (name.as_str().to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned()))
+            .collect();
+        let body = response.text().await.unwrap_or_default();
+        Ok(RecordedResponse { status, headers, final_url, redirects, body })
+    }
+
+    /// Send a request, following redirects by hand so that every hop can be recorded. As browsers
+    /// do, 307 and 308 repeat the method and body while other redirects continue as a bodiless GET,
+    /// and the request's own headers (tokens, cookies) are only sent again to the same origin.
+    /// Every host on the way waits for its politeness budget, `limits` for the first and the
+    /// defaults for the rest; only the current host's permit is held, and it is returned so the
+    /// caller can keep it until the body has been read.
+    async fn send_following(&self, client: &Client, request: &ProbeRequest, limits: HostLimits) -> Result<(reqwest::Response, Vec<Redirect>, HostPermit), FetchError> {
+        let origin = reqwest::Url::parse(&request.url).ok().map(|url| url.origin());
+        let mut host = host_of(&request.url);
+        let mut permit = self.scheduler.acquire(&request.url, limits).await;
+        let mut method = request.method.clone();
+        let mut with_body = true;
+        let mut response = request.builder(client, &request.url, method.clone(), true, true).send().await.map_err(FetchError::Network)?;
+        let mut redirects = Vec::new();
+        while response.status().is_redirection() {
+            // A redirect without a usable Location is left for the detection logic to judge
+            let Some(location) = response.headers().get(reqwest::header::LOCATION).and_then(|v| v.to_str().ok()) else {
+                break;
+            };
+            let Ok(next) = response.url().join(location) else {
+                break;
+            };
+            if redirects.len() == MAX_REDIRECTS {
+                return Err(FetchError::Other(format!("too many redirects (more than {})", MAX_REDIRECTS)));
+            }
+            redirects.push(Redirect {
+                url: response.url().to_string(),
+                status: response.status().as_u16(),
+                location: location.to_string(),
+            });
+
+            if !matches!(response.status(), StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT) {
+                if method != reqwest::Method::HEAD {
+                    method = reqwest::Method::GET;
+                }
+                with_body = false;
+            }
+            // Let go of the previous host before waiting for the next, so two chains crossing
+            // the same hosts in opposite directions can't wait on each other
+            let next_host = host_of(next.as_str());
+            if next_host != host {
+                drop(permit);
+                permit = self.scheduler.acquire(next.as_str(), self.host_limits(None)).await;
+                host = next_host;
+            }
+            let same_origin = origin.as_ref() == Some(&next.origin());
+            response = request
+                .builder(client, next.as_str(), method.clone(), with_body, same_origin)
+                .send()
+                .await
+                .map_err(FetchError::Network)?;
+        }
+        Ok((response, redirects, permit))
+    }
+
+    /// How long to wait before the next attempt, or None to give up.
+    /// Exponential backoff with jitter, stretched to honour Retry-After.
+    /// Replayed responses never change, so replay mode never retries.
+    fn retry_delay(&self, attempt: u32, result: &CheckResult) -> Option<Duration> {
+        if attempt > self.options.retries || !result.is_transient() || matches!(self.options.fixtures, Some(FixtureMode::Replay(_))) {
+            return None;
+        }
+
+        let backoff = self.options.retry_delay
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(MAX_BACKOFF);
+        // Equal jitter: a random wait between half and all of the backoff, so parallel retries spread out
+        let jittered = backoff.mul_f64(rand::random::<f64>() * 0.5 + 0.5);
 
-        Self { client }
+        match result {
+            CheckResult::RateLimited { retry_after_secs: Some(secs) } => {
+                let retry_after = Duration::from_secs(*secs);
+                if retry_after > MAX_RETRY_AFTER {
+                    None
+                } else {
+                    Some(retry_after.max(jittered))
+                }
+            }
+            _ => Some(jittered),
+        }
     }
     
     /// Detect JavaScript redirects using regex pattern matching
     /// This is much lighter than a full headless browser and catches common redirect patterns
     /// without needing to execute complex JavaScript
-    fn check_js_redirects(&self, html: &str, username: &str, final_url: &str) -> Option<CheckResult> {
+    fn check_js_redirects(&self, html: &str, username: &str, final_url: &str, evidence: &mut Vec<Evidence>) -> Option<CheckResult> {
         // Quick check: if no location/redirect patterns, skip
         let html_lower = html.to_lowercase();
         if !html_lower.contains("location") && 
@@ -90,6 +603,7 @@ impl AccountChecker {
                            target.contains("/error") ||
                            target.contains("404") ||
                            target.contains("notfound") {
+                            evidence.push(Evidence::not_found(EvidenceSource::JsRedirect, 0.85, format!("script redirects to error page \"{}\"", target)));
                             return Some(CheckResult::NotFound);
                         }
                     }
@@ -118,6 +632,7 @@ impl AccountChecker {
                             // But be conservative - only flag if it's clearly an error path
                             if target.contains("404") || target.contains("error") || 
                                target.contains("not-found") || target == "/" {
+                                evidence.push(Evidence::not_found(EvidenceSource::JsRedirect, 0.7, format!("script redirects away from the profile to \"{}\"", target)));
                                 return Some(CheckResult::NotFound);
                             }
                         }
@@ -129,441 +644,349 @@ impl AccountChecker {
         None
     }
     
-    /// Site-specific checks for known problematic sites
-    /// Returns Some(CheckResult) if site-specific logic determines result, None otherwise
-    fn check_site_specific(
+    /// Evaluate the site's declarative rules (see `sites::Rules`)
+    /// Returns Some(CheckResult) if the rules determine the result, None to fall through to generic detection
+    fn check_rules(
         &self,
-        url: &str,
-        body: &str,
+        site: &Site,
         body_lower: &str,
         username: &str,
-        final_url: &str,
         status_code: u16,
+        evidence: &mut Vec<Evidence>,
     ) -> Option<CheckResult> {
+        let rules = &site.rules;
+        if rules.is_empty() {
+            return None;
+        }
         let username_lower = username.to_lowercase();
-        let final_url_lower = final_url.to_lowercase();
-        let url_lower = url.to_lowercase();
-        
-        // Badoo: Returns 200 for non-existent users with empty SPA shell
-        // Badoo pages are SPA - if username doesn't appear anywhere in initial HTML, user doesn't exist
-        if url_lower.contains("badoo.com") || final_url_lower.contains("badoo.com") {
-            // Check if username appears in any meaningful way (title, og:title, meta, body content)
-            let username_in_title = if let (Some(title_start), Some(title_end)) = 
-                (body_lower.find("<title>"), body_lower.find("</title>")) {
-                if title_start + 7 < title_end {
-                    body_lower[title_start + 7..title_end].contains(&username_lower)
-                } else {
-                    false
-                }
-            } else {
-                false
-            };
-            
-            let username_in_og_title = body_lower.contains("property=\"og:title\"") && 
-                                      body_lower.contains(&username_lower);
-            
-            // Check if username appears in body content (not just in scripts/CSS)
-            // Extract text content by removing script/style tags
-            let mut text_content = body_lower.to_string();
-            // Remove script tags
-            while let Some(start) = text_content.find("<script") {
-                if let Some(end) = text_content[start..].find("</script>") {
-                    let end_pos = start + end + 9;
-                    text_content.replace_range(start..end_pos.min(text_content.len()), "");
-                } else {
-                    break;
-                }
-            }
-            // Remove style tags
-            while let Some(start) = text_content.find("<style") {
-                if let Some(end) = text_content[start..].find("</style>") {
-                    let end_pos = start + end + 8;
-                    text_content.replace_range(start..end_pos.min(text_content.len()), "");
-                } else {
-                    break;
-                }
-            }
-            
-            let username_in_content = text_content.contains(&username_lower);
-            
-            // If username doesn't appear in title, og:title, or content, user doesn't exist
-            if !username_in_title && !username_in_og_title && !username_in_content {
-                return Some(CheckResult::NotFound);
-            }
+
+        if rules.error_codes.contains(&status_code) {
+            evidence.push(Evidence::not_found(EvidenceSource::SiteRules, 0.9, format!("HTTP {} is an error code for this site", status_code)));
+            return Some(CheckResult::NotFound);
         }
-        
-        // Glitch: Generic title means user doesn't exist
-        if url_lower.contains("glitch.com") || final_url_lower.contains("glitch.com") {
-            let title_is_generic = if let (Some(title_start), Some(title_end)) = 
-                (body_lower.find("<title>"), body_lower.find("</title>")) {
-                if title_start + 7 < title_end {
-                    let title = &body_lower[title_start + 7..title_end];
-                    // Generic Glitch titles don't contain username
-                    title.contains("glitch: the friendly community") || 
-                    (title.contains("glitch") && !title.contains(&username_lower))
-                } else {
-                    false
-                }
-            } else {
-                false
-            };
-            
-            // Check og:title as well
-            let og_title_is_generic = body_lower.contains("property=\"og:title\"") && 
-                                     (body_lower.contains("glitch: the friendly community") ||
-                                      (!body_lower.contains(&username_lower) && 
-                                       body_lower.contains("property=\"og:title\"") &&
-                                       body_lower.contains("glitch")));
-            
-            if title_is_generic || og_title_is_generic {
-                return Some(CheckResult::NotFound);
-            }
+
+        if rules.blocked_codes.contains(&status_code) {
+            evidence.push(Evidence::inconclusive(EvidenceSource::SiteRules, format!("HTTP {} is a blocked code for this site", status_code)));
+            return Some(CheckResult::Blocked(format!("{} refuses to answer without authentication (HTTP {})", site.name, status_code)));
         }
-        
-        // TopCoder: If meta refresh to profiles.topcoder.com exists, check if page is generic
-        // TopCoder redirects via meta refresh - non-existent users get generic/empty content
-        if url_lower.contains("topcoder.com") || final_url_lower.contains("topcoder.com") {
-            let has_meta_refresh = body_lower.contains("http-equiv=\"refresh\"") || 
-                                   body_lower.contains("http-equiv='refresh'");
-            let refreshes_to_profiles = body_lower.contains("profiles.topcoder.com");
-            
-            // Extract title
-            let title_text = if let (Some(title_start), Some(title_end)) = 
-                (body_lower.find("<title>"), body_lower.find("</title>")) {
-                if title_start + 7 < title_end {
-                    Some(&body_lower[title_start + 7..title_end])
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-            
-            // If there's a meta refresh to profiles.topcoder.com, check if user exists
-            // TopCoder always redirects members -> profiles, but non-existent users show generic content
-            if has_meta_refresh && refreshes_to_profiles {
-                // Check if title is generic or missing
-                if let Some(title) = title_text {
-                    // Generic titles mean user doesn't exist
-                    let is_generic = (title.contains("topcoder") && !title.contains(&username_lower)) ||
-                                    title.contains("top technology talent") ||
-                                    title.trim() == "topcoder";
-                    if is_generic {
-                        return Some(CheckResult::NotFound);
-                    }
-                } else {
-                    // No title tag found + meta refresh = non-existent user
-                    // TopCoder pages always have a title, so no title = error/generic page
-                    return Some(CheckResult::NotFound);
-                }
-            }
-            
-            // Also check if already on profiles.topcoder.com with generic title
-            if final_url_lower.contains("profiles.topcoder.com") {
-                if let Some(title) = title_text {
-                    let is_generic = (title.contains("topcoder") && !title.contains(&username_lower)) ||
-                                    title.contains("top technology talent");
-                    if is_generic {
-                        return Some(CheckResult::NotFound);
-                    }
-                }
-            }
+
+        if rules.invalid_codes.contains(&status_code) {
+            evidence.push(Evidence::inconclusive(EvidenceSource::SiteRules, format!("HTTP {} is an invalid-username code for this site", status_code)));
+            return Some(CheckResult::InvalidUsername(format!("{} rejected the username (HTTP {})", site.name, status_code)));
         }
-        
-        // AngelList/Wellfound: Check if redirected to wellfound.com and it's blocked/404
-        if url_lower.contains("angel.co") && final_url_lower.contains("wellfound.com") {
-            // If redirected to wellfound and it's 403 (Cloudflare) or body is empty/generic, not found
-            if status_code == 403 || 
-               body_lower.contains("please enable js") ||
-               body_lower.len() < 1000 {
-                return Some(CheckResult::NotFound);
-            }
+
+        if let Some(marker) = rules.absence.iter().find(|s| body_lower.contains(&s.to_lowercase())) {
+            evidence.push(Evidence::not_found(EvidenceSource::SiteRules, 0.9, format!("page contains absence marker \"{}\"", marker)));
+            return Some(CheckResult::NotFound);
         }
-        
-        // eBay: Aggressive check - if username doesn't appear in content, user doesn't exist
-        // eBay shows security pages, 503 errors, or empty pages for non-existent users
-        if url_lower.contains("ebay.com") || final_url_lower.contains("ebay.com") {
-            // Check for security/captcha patterns
-            if body_lower.contains("security measure") ||
-               body_lower.contains("security | ebay") ||
-               body_lower.contains("captcha_form") ||
-               body_lower.contains("id=captcha_form") ||
-               body_lower.contains("please verify yourself") ||
-               body_lower.contains("verify yourself to continue") ||
-               body_lower.contains("service unavailable") {
-                return Some(CheckResult::NotFound);
+
+        if let Some(marker) = rules.generic_title.iter().find(|s| {
+            Self::page_title(body_lower).is_some_and(|title| title.contains(&s.to_lowercase()) && !title.contains(&username_lower))
+        }) {
+            evidence.push(Evidence::not_found(EvidenceSource::SiteRules, 0.85, format!("page title is the site's generic \"{}\" title", marker)));
+            return Some(CheckResult::NotFound);
+        }
+
+        if rules.found_codes.contains(&status_code) {
+            // Found codes are a fallback for sites that hide everything - they carry little information
+            evidence.push(Evidence::found(EvidenceSource::SiteRules, 0.6, format!("HTTP {} is a found code for this site", status_code)));
+            return Some(CheckResult::Found);
+        }
+
+        // Proof that the account exists: a presence marker or the username in a required location
+        let presence = rules.presence.iter()
+            .find(|s| body_lower.contains(&s.to_lowercase().replace("{}", &username_lower)));
+        let location = rules.username_in.iter()
+            .find(|location| Self::username_at(**location, body_lower, &username_lower));
+        if let Some(marker) = presence {
+            evidence.push(Evidence::found(EvidenceSource::SiteRules, 0.85, format!("page contains presence marker \"{}\"", marker)));
+        }
+        if let Some(location) = location {
+            evidence.push(Evidence::found(EvidenceSource::SiteRules, 0.9, format!("username appears in {}", location.describe())));
+        }
+        let has_proof = presence.is_some() || location.is_some();
+
+        if rules.require_marker && !has_proof {
+            // A server error is the API being down, not the API changing
+            if (500..600).contains(&status_code) {
+                evidence.push(Evidence::inconclusive(EvidenceSource::Status, format!("HTTP {}", status_code)));
+                return Some(CheckResult::Unavailable(format!("{} answered HTTP {}", site.name, status_code)));
             }
-            
-            // For eBay, if username doesn't appear in any meaningful way, user doesn't exist
-            // Valid eBay user pages always contain the username prominently
-            let username_in_url_path = final_url_lower.contains(&format!("/usr/{}", username_lower)) ||
-                                      url_lower.contains(&format!("/usr/{}", username_lower));
-            
-            // Check if username appears in visible content (not just in URL or scripts)
-            let username_in_content = body_lower.contains(&format!(">{}</", username_lower)) ||
-                                     body_lower.contains(&format!(">{}</a>", username_lower)) ||
-                                     body_lower.contains(&format!(">{}</h", username_lower)) ||
-                                     body_lower.contains(&format!(">{}</span>", username_lower)) ||
-                                     (body_lower.contains("property=\"og:title\"") && body_lower.contains(&username_lower));
-            
-            // If username is in URL path but NOT in content, it's likely a security/error page
-            // For non-existent users, eBay shows pages without the username in content
-            if username_in_url_path && !username_in_content {
-                return Some(CheckResult::NotFound);
+            evidence.push(Evidence::inconclusive(EvidenceSource::SiteRules, format!("HTTP {} response matches none of the site's markers", status_code)));
+            return Some(CheckResult::Error(format!("unrecognised response from {} (HTTP {})", site.name, status_code)));
+        }
+
+        match rules.min_body_size {
+            // Small pages are empty shells/error pages unless they prove the account exists
+            Some(min_size) if body_lower.len() < min_size && !has_proof => {
+                evidence.push(Evidence::not_found(EvidenceSource::SiteRules, 0.75, format!("{} byte page is below the {} byte minimum", body_lower.len(), min_size)));
+                Some(CheckResult::NotFound)
             }
-            
-            // Also check: if page is suspiciously small or generic, likely doesn't exist
-            if body.len() < 5000 && !username_in_content {
-                return Some(CheckResult::NotFound);
+            Some(_) => None,
+            // Without a size threshold, proof markers are mandatory
+            None if rules.has_proof_markers() && !has_proof => {
+                evidence.push(Evidence::not_found(EvidenceSource::SiteRules, 0.8, "no presence marker or username in the required page locations"));
+                Some(CheckResult::NotFound)
             }
+            None => None,
         }
-        
-        // Etsy: Check if username appears in title or content
-        // Etsy returns 200 for non-existent shops with generic content
-        if url_lower.contains("etsy.com") || final_url_lower.contains("etsy.com") {
-            // Check if title contains username - valid shops have username in title
-            let title_has_username = if let (Some(title_start), Some(title_end)) = 
-                (body_lower.find("<title>"), body_lower.find("</title>")) {
-                if title_start + 7 < title_end {
-                    body_lower[title_start + 7..title_end].contains(&username_lower)
+    }
+
+    /// Whether the (lowercased) username appears at a given location of a lowercased page
+    fn username_at(location: UsernameLocation, body_lower: &str, username_lower: &str) -> bool {
+        match location {
+            UsernameLocation::Title => Self::page_title(body_lower)
+                .is_some_and(|title| title.contains(username_lower)),
+            // SEO pages rarely put the handle in og:title verbatim, so any mention will do
+            UsernameLocation::OgTitle => (body_lower.contains("property=\"og:title\"") ||
+                                          body_lower.contains("property='og:title'")) &&
+                                         body_lower.contains(username_lower),
+            UsernameLocation::Text => Self::visible_text(body_lower).contains(username_lower),
+        }
+    }
+
+    /// Contents of the first `<title>` tag
+    fn page_title(body_lower: &str) -> Option<&str> {
+        let title_start = body_lower.find("<title>")?;
+        let title_end = body_lower.find("</title>")?;
+        if title_start + 7 < title_end {
+            Some(&body_lower[title_start + 7..title_end])
+        } else {
+            None
+        }
+    }
+
+    /// Page text with `<script>` and `<style>` blocks removed
+    fn visible_text(body_lower: &str) -> String {
+        let mut text_content = body_lower.to_string();
+        for (open, close) in [("<script", "</script>"), ("<style", "</style>")] {
+            while let Some(start) = text_content.find(open) {
+                if let Some(end) = text_content[start..].find(close) {
+                    let end_pos = start + end + close.len();
+                    text_content.replace_range(start..end_pos.min(text_content.len()), "");
                 } else {
-                    false
+                    break;
                 }
-            } else {
-                false
-            };
-            
-            // Check if username appears in visible content
-            let username_in_content = body_lower.contains(&format!("/shop/{}", username_lower)) ||
-                                     body_lower.contains(&format!(">{}</", username_lower)) ||
-                                     (body_lower.contains("property=\"og:title\"") && body_lower.contains(&username_lower));
-            
-            // If username not in title or content, shop doesn't exist
-            if !title_has_username && !username_in_content {
-                return Some(CheckResult::NotFound);
             }
         }
-        
-        // Steam: Check for "Profile Not Found" or similar
-        if url_lower.contains("steamcommunity.com") || final_url_lower.contains("steamcommunity.com") {
-            // Check for error messages in content
-            if body_lower.contains("profile not found") ||
-               body_lower.contains("could not find") ||
-               body_lower.contains("invalid profile") ||
-               body_lower.contains("profile error") {
-                return Some(CheckResult::NotFound);
-            }
-            
-            // Check if username appears in profile link/header - valid profiles have username visible
-            let username_in_profile = body_lower.contains(&format!("profile/{}", username_lower)) ||
-                                     body_lower.contains(&format!("id/{}", username_lower)) ||
-                                     body_lower.contains(&format!(">{}</", username_lower));
-            
-            // If no username in profile indicators and page is generic, likely doesn't exist
-            if !username_in_profile && body_lower.len() < 50000 {
-                return Some(CheckResult::NotFound);
-            }
+        text_content
+    }
+
+    /// Check one site, retrying transient failures according to the checker's options
+    pub async fn check_account(&self, site: &Site, username: &str) -> SiteResult {
+        if let Some(reason) = &site.unsupported {
+            return Self::unrequested(site, username, CheckResult::Unsupported(reason.clone()), reason.clone());
         }
-        
-        // Instagram: Pure SPA - check if username appears in og:title or title
-        // Instagram doesn't show error messages in initial HTML for non-existent users
-        // Valid profiles have username in og:title or title tag
-        if url_lower.contains("instagram.com") || final_url_lower.contains("instagram.com") {
-            // Check for explicit error messages (sometimes present)
-            if body_lower.contains("sorry, this page isn't available") ||
-               body_lower.contains("page isn't available") ||
-               body_lower.contains("user not found") {
-                return Some(CheckResult::NotFound);
-            }
-            
-            // Instagram is a pure SPA - check if username appears in SEO tags
-            // Valid profiles have username in og:title
-            let has_og_title = body_lower.contains("property=\"og:title\"") || 
-                              body_lower.contains("property='og:title'");
-            let username_in_og_title = has_og_title && body_lower.contains(&username_lower);
-            
-            // Check title tag
-            let title_has_username = if let (Some(title_start), Some(title_end)) = 
-                (body_lower.find("<title>"), body_lower.find("</title>")) {
-                if title_start + 7 < title_end {
-                    body_lower[title_start + 7..title_end].contains(&username_lower)
-                } else {
-                    false
+        // Don't ask a site about a handle it could never have issued
+        if let Some(pattern) = &site.username_pattern {
+            if let Ok(regex) = regex::Regex::new(pattern) {
+                if !regex.is_match(username) {
+                    let detail = format!("username does not match {}'s handle format ({})", site.name, pattern);
+                    return Self::unrequested(site, username, CheckResult::InvalidUsername(detail.clone()), detail);
                 }
-            } else {
-                false
-            };
-            
-            // For Instagram SPAs, if username doesn't appear in og:title or title, user doesn't exist
-            // Instagram profiles always have username in og:title or title for SEO
-            if !username_in_og_title && !title_has_username {
-                return Some(CheckResult::NotFound);
             }
         }
-        
-        // Threads (Meta/Facebook): Check for generic error pages
-        if url_lower.contains("threads.net") || final_url_lower.contains("threads.net") {
-            // Threads shows generic error for non-existent users
-            if body_lower.contains("page not found") ||
-               body_lower.contains("content isn't available") ||
-               body_lower.contains("this page isn't available") ||
-               (body_lower.contains("threads") && !body_lower.contains(&username_lower) && body_lower.len() < 30000) {
-                return Some(CheckResult::NotFound);
-            }
-            
-            // Check if username appears in meta tags
-            let username_in_meta = (body_lower.contains("property=\"og:title\"") || 
-                                   body_lower.contains("property='og:title'")) &&
-                                  body_lower.contains(&username_lower);
-            
-            if !username_in_meta && body_lower.contains("threads") {
-                return Some(CheckResult::NotFound);
+
+        let mut retries = Vec::new();
+        let mut attempt = 1;
+        loop {
+            let mut result = self.check_account_once(site, username).await;
+            match self.retry_delay(attempt, &result.result) {
+                Some(delay) => {
+                    retries.push(Evidence::inconclusive(
+                        EvidenceSource::Retry,
+                        format!("attempt {}: {} - retrying in {:.1}s", attempt, result.result, delay.as_secs_f64()),
+                    ));
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                None => {
+                    result.attempts = attempt;
+                    retries.append(&mut result.evidence);
+                    result.evidence = retries;
+                    return result;
+                }
             }
         }
-        
-        // Weibo: Check for error pages or missing content
-        if url_lower.contains("weibo.com") || final_url_lower.contains("weibo.com") {
-            // Check if username appears in content - valid profiles have username visible
-            let username_in_content = body_lower.contains(&format!(">{}</", username_lower)) ||
-                                     body_lower.contains(&format!("/{}</", username_lower)) ||
-                                     (body_lower.contains("property=\"og:title\"") && body_lower.contains(&username_lower));
-            
-            // If username doesn't appear and page seems empty/generic, likely doesn't exist
-            if !username_in_content && body_lower.len() < 20000 {
-                return Some(CheckResult::NotFound);
-            }
+    }
+
+    /// The result for a check settled from the catalogue alone, without sending a request
+    fn unrequested(site: &Site, username: &str, result: CheckResult, detail: String) -> SiteResult {
+        SiteResult {
+            username: username.to_string(),
+            seed: None,
+            site: site.name.clone(),
+            url: site.url.replace("{}", username),
+            category: site.category.clone(),
+            result,
+            status_code: None,
+            final_url: None,
+            attempts: 0,
+            confidence: 0.0,
+            evidence: vec![Evidence::inconclusive(EvidenceSource::SiteRules, detail)],
+            profile: None,
         }
+    }
+
+    async fn check_account_once(&self, site: &Site, username: &str) -> SiteResult {
+        let url = site.url.replace("{}", username);
         
-        // Battle.net: Check for error pages or invalid profile indicators
-        if url_lower.contains("blizzard.com") || final_url_lower.contains("blizzard.com") ||
-           url_lower.contains("battle.net") || final_url_lower.contains("battle.net") {
-            // Check for error messages
-            if body_lower.contains("page not found") ||
-               body_lower.contains("invalid") ||
-               body_lower.contains("error") ||
-               (body_lower.len() < 10000 && !body_lower.contains(&username_lower)) {
-                return Some(CheckResult::NotFound);
+        // Use URL redirect detection - this catches false positives by checking if URL changed
+        let mut evidence = Vec::new();
+        let mut response = ResponseMeta::default();
+        let request = ProbeRequest::for_site(site, username);
+        let result = self.check_url(site, &request, username, false, &mut evidence, &mut response).await;
+        if let Some(recorded) = response.recorded.take() {
+            self.record_fixture(&site.name, username, &request.url, &result, recorded, &mut evidence);
+        }
+        let confidence = confidence_for(&result, &evidence);
+        let mut profile = match (&result, &response.body) {
+            (CheckResult::Found, Some(body)) => {
+                let page_url = response.final_url.as_deref().unwrap_or(&url);
+                Some(profile::extract(body, page_url, &site.profile)).filter(|p| !p.is_empty())
             }
-            
-            // Check if username appears in content
-            let username_in_content = body_lower.contains(&format!(">{}</", username_lower)) ||
-                                     body_lower.contains(&format!("/{}</", username_lower));
-            
-            if !username_in_content && body_lower.len() < 15000 {
-                return Some(CheckResult::NotFound);
+            _ => None,
+        };
+        if self.options.hash_avatars {
+            if let Some(profile) = profile.as_mut() {
+                if let Some(avatar_url) = &profile.avatar_url {
+                    // A missing or undecodable avatar just leaves the account out of the clustering
+                    profile.avatar_hash = self.hash_avatar(site, avatar_url).await.ok().map(avatar::format_hash);
+                }
             }
         }
         
-        None
+        SiteResult {
+            username: username.to_string(),
+            seed: None,
+            site: site.name.clone(),
+            url,
+            category: site.category.clone(),
+            result,
+            status_code: response.status_code,
+            final_url: response.final_url,
+            attempts: 1,
+            confidence,
+            evidence,
+            profile,
+        }
     }
 
-    pub async fn check_account(&self, site: &Site, username: &str) -> SiteResult {
-        // Special handling for Discord since it uses IDs, not usernames in URLs
-        if site.name == "Discord" {
-            return self.check_discord_username(username).await;
+    /// Save the response behind a result as a fixture that expects that result
+    fn record_fixture(&self, site_name: &str, username: &str, url: &str, result: &CheckResult, response: RecordedResponse, evidence: &mut Vec<Evidence>) {
+        let Some(FixtureMode::Record(dir)) = &self.options.fixtures else {
+            return;
+        };
+        let fixture = Fixture {
+            site: site_name.to_string(),
+            username: username.to_string(),
+            url: url.to_string(),
+            expect: result.tag().to_string(),
+            recorded_at: Some(httpdate::fmt_http_date(SystemTime::now())),
+            response,
+        };
+        if let Err(e) = fixture::save(dir, &fixture) {
+            evidence.push(Evidence::inconclusive(EvidenceSource::Network, format!("could not record fixture: {}", e)));
         }
-        
-        // Remove or skip sites that are shut down
-        if site.name == "Mixer" {
-            return SiteResult {
-                site: site.name.clone(),
-                url: site.url.replace("{}", username),
-                category: site.category.clone(),
-                result: CheckResult::Error("Mixer was shut down in 2020.".to_string()),
-            };
+    }
+
+    /// Download an avatar and compute its perceptual hash
+    async fn hash_avatar(&self, site: &Site, avatar_url: &str) -> Result<u64, String> {
+        if matches!(self.options.fixtures, Some(FixtureMode::Replay(_))) {
+            return Err("avatars are not replayed".to_string());
         }
-        
-        // Skip Spotify Artist - uses IDs, not usernames
-        if site.name == "Spotify Artist" {
-            return SiteResult {
-                site: site.name.clone(),
-                url: site.url.replace("{}", username),
-                category: site.category.clone(),
-                result: CheckResult::Error("Spotify Artist URLs use IDs, not usernames.".to_string()),
-            };
+        let client = self.client_for(&site.name);
+        let (response, _, _permit) = self
+            .send_following(client, &ProbeRequest::get(avatar_url), self.host_limits(None))
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("HTTP {}", response.status().as_u16()));
         }
-        
-        let url = site.url.replace("{}", username);
-        
-        // Use URL redirect detection - this catches false positives by checking if URL changed
-        let result = self.check_url(&url, username, &site.name, false).await;
-        
-        match result {
-            CheckResult::Found => SiteResult {
-                site: site.name.clone(),
-                url: url.clone(),
-                category: site.category.clone(),
-                result: CheckResult::Found,
-            },
-            CheckResult::NotFound => SiteResult {
-                site: site.name.clone(),
-                url: url.clone(),
-                category: site.category.clone(),
-                result: CheckResult::NotFound,
-            },
-            CheckResult::Error(e) => SiteResult {
-                site: site.name.clone(),
-                url: url.clone(),
-                category: site.category.clone(),
-                result: CheckResult::Error(e),
-            },
-            CheckResult::Timeout => SiteResult {
-                site: site.name.clone(),
-                url: url.clone(),
-                category: site.category.clone(),
-                result: CheckResult::Timeout,
-            },
+        if response.content_length().is_some_and(|length| length as usize > avatar::MAX_AVATAR_BYTES) {
+            return Err("avatar is too large".to_string());
+        }
+        let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+        if bytes.len() > avatar::MAX_AVATAR_BYTES {
+            return Err("avatar is too large".to_string());
         }
+        avatar::dhash(&bytes)
     }
 
-
-    async fn check_url(&self, url: &str, username: &str, site_name: &str, _is_spa: bool) -> CheckResult {
+    async fn check_url(
+        &self,
+        site: &Site,
+        request: &ProbeRequest,
+        username: &str,
+        _is_spa: bool,
+        evidence: &mut Vec<Evidence>,
+        meta: &mut ResponseMeta,
+    ) -> CheckResult {
+        let url = request.url.as_str();
         let url_lower = url.to_lowercase();
         // Check if URL redirects (many sites redirect 404s to error pages)
-        let response = match self.client.get(url).send().await {
+        let response = match self.fetch(&site.name, username, self.host_limits(Some(site)), request).await {
             Ok(resp) => resp,
-            Err(e) => {
+            Err(FetchError::Other(message)) => {
+                evidence.push(Evidence::inconclusive(EvidenceSource::Network, message.clone()));
+                return CheckResult::Error(message);
+            }
+            Err(FetchError::Network(e)) => {
                 if e.is_timeout() {
+                    evidence.push(Evidence::inconclusive(EvidenceSource::Network, "request timed out"));
                     return CheckResult::Timeout;
                 }
                 // Handle DNS errors and SSL errors more gracefully
                 let error_msg = e.to_string();
+                evidence.push(Evidence::inconclusive(EvidenceSource::Network, error_msg.clone()));
                 if error_msg.contains("dns error") || error_msg.contains("failed to lookup address") {
-                    return CheckResult::Error("DNS error: Site may be down or domain changed".to_string());
+                    return CheckResult::DnsError("Site may be down or domain changed".to_string());
                 } else if error_msg.contains("certificate verify failed") || error_msg.contains("SSL") {
-                    return CheckResult::Error("SSL certificate error: Site may have certificate issues".to_string());
+                    return CheckResult::TlsError("Site may have certificate issues".to_string());
                 }
-                return CheckResult::Error(format!("Network error: {}", e));
+                return CheckResult::Unavailable(format!("Network error: {}", e));
             }
         };
 
-        let status = response.status();
-        let final_url = response.url().as_str().to_string(); // Clone to avoid borrow issues
+        if matches!(self.options.fixtures, Some(FixtureMode::Record(_))) {
+            meta.recorded = Some(response.clone());
+        }
+        let Ok(status) = StatusCode::from_u16(response.status) else {
+            let message = format!("invalid HTTP status {}", response.status);
+            evidence.push(Evidence::inconclusive(EvidenceSource::Network, message.clone()));
+            return CheckResult::Error(message);
+        };
+        let final_url = response.final_url.clone();
+        let retry_after = parse_retry_after(response.header("retry-after"));
+        meta.status_code = Some(status.as_u16());
+        meta.final_url = Some(final_url.clone());
         
         // Check for redirect responses (3xx status codes)
         if status.is_redirection() {
-            if let Some(location) = response.headers().get("location") {
-                if let Ok(location_str) = location.to_str() {
-                    let location_lower = location_str.to_lowercase();
-                    let username_lower = username.to_lowercase();
-                    let url_lower = url.to_lowercase();
-                    
-                    // If redirect location doesn't contain username, it's likely a 404 redirect
-                    if url_lower.contains(&username_lower) && !location_lower.contains(&username_lower) {
-                        return CheckResult::NotFound;
-                    }
-                    
-                    // Check if redirecting to error pages
-                    if location_lower.contains("404") || 
-                       location_lower.contains("not-found") || 
-                       location_lower.contains("/error") {
-                        return CheckResult::NotFound;
-                    }
-                    // If it's a redirect but location contains username, it's likely found
-                    return CheckResult::Found;
+            if let Some(location_str) = response.header("location") {
+                let location_lower = location_str.to_lowercase();
+                let username_lower = username.to_lowercase();
+                let url_lower = url.to_lowercase();
+                
+                // If redirect location doesn't contain username, it's likely a 404 redirect
+                if url_lower.contains(&username_lower) && !location_lower.contains(&username_lower) {
+                    evidence.push(Evidence::not_found(EvidenceSource::Redirect, 0.8, format!("HTTP {} to \"{}\" drops the username", status.as_u16(), location_str)));
+                    return CheckResult::NotFound;
+                }
+                
+                // Check if redirecting to error pages
+                if location_lower.contains("404") || 
+                   location_lower.contains("not-found") || 
+                   location_lower.contains("/error") {
+                    evidence.push(Evidence::not_found(EvidenceSource::Redirect, 0.9, format!("HTTP {} to error page \"{}\"", status.as_u16(), location_str)));
+                    return CheckResult::NotFound;
                 }
+                // If it's a redirect but location contains username, it's likely found
+                evidence.push(Evidence::found(EvidenceSource::Redirect, 0.6, format!("HTTP {} to \"{}\" keeps the username", status.as_u16(), location_str)));
+                return CheckResult::Found;
             }
             // Redirect but can't parse - default to found
+            evidence.push(Evidence::found(EvidenceSource::Redirect, 0.3, format!("HTTP {} without a readable Location header", status.as_u16())));
             return CheckResult::Found;
         }
         
@@ -583,11 +1006,13 @@ impl AccountChecker {
                final_url_lower.contains("user-not-found") ||
                final_url_lower.contains("/not_found") ||
                final_url_lower.contains("/error") {
+                evidence.push(Evidence::not_found(EvidenceSource::Redirect, 0.9, format!("redirected to error page {}", final_url)));
                 return CheckResult::NotFound;
             }
             
-            // Special case: Giphy redirects user profiles to /explore/ (search page, not user)
-            if url_lower.contains("giphy.com") && final_url_lower.contains("/explore/") {
+            // Site redirects away from missing profiles (e.g. Giphy sends them to /explore/)
+            if let Some(pattern) = site.rules.redirect_away.iter().find(|s| final_url_lower.contains(&s.to_lowercase())) {
+                evidence.push(Evidence::not_found(EvidenceSource::SiteRules, 0.9, format!("redirected to {} (matches \"{}\")", final_url, pattern)));
                 return CheckResult::NotFound;
             }
             
@@ -603,6 +1028,7 @@ impl AccountChecker {
                 
                 // If original URL had username in path but final doesn't, it's a redirect away (404)
                 if url_path_contains_username && !final_path_contains_username {
+                    evidence.push(Evidence::not_found(EvidenceSource::Redirect, 0.85, format!("redirected to {}, which drops the username from the path", final_url)));
                     return CheckResult::NotFound;
                 }
             }
@@ -612,6 +1038,7 @@ impl AccountChecker {
             if url_lower.contains(&username_lower) && !final_url_lower.contains(&username_lower) {
                 // Username missing from final URL = almost certainly a redirect away (404)
                 // Don't require specific patterns - if username is gone, it's not found
+                evidence.push(Evidence::not_found(EvidenceSource::Redirect, 0.85, format!("redirected to {}, which drops the username", final_url)));
                 return CheckResult::NotFound;
             }
             
@@ -623,6 +1050,7 @@ impl AccountChecker {
                 // Domain changed - check if it's a known redirect pattern (like angel.co -> wellfound.com)
                 // and if the final URL doesn't contain username, it's likely 404
                 if !final_url_lower.contains(&username_lower) {
+                    evidence.push(Evidence::not_found(EvidenceSource::Redirect, 0.85, format!("redirected to another domain without the username: {}", final_url)));
                     return CheckResult::NotFound;
                 }
                 // Special case: angel.co redirects to wellfound.com
@@ -634,8 +1062,11 @@ impl AccountChecker {
             }
         }
 
-        let body_text = response.text().await.unwrap_or_default();
+        let body_text = response.body;
         let body_lower = body_text.to_lowercase();
+        if self.options.extract_profiles {
+            meta.body = Some(body_text.clone());
+        }
         let final_url_lower = final_url.to_lowercase();
         let username_lower = username.to_lowercase();
 
@@ -644,24 +1075,13 @@ impl AccountChecker {
            body_lower.contains("just a moment") ||
            body_lower.contains("checking your browser") ||
            (body_lower.contains("cloudflare") && body_lower.contains("cf-challenge")) {
-            return CheckResult::Error("Cloudflare protection (cannot verify)".to_string());
+            evidence.push(Evidence::inconclusive(EvidenceSource::Status, "Cloudflare challenge page"));
+            return CheckResult::Challenge("Cloudflare protection (cannot verify)".to_string());
         }
         
-        // eBay security check - must run early, before other checks
-        // eBay shows security/captcha pages for non-existent users - these patterns indicate user doesn't exist
-        if final_url_lower.contains("ebay.com") || url_lower.contains("ebay.com") {
-            // Simple body check - if ANY security/captcha pattern found, user doesn't exist
-            if body_lower.contains("security measure") ||
-               body_lower.contains("captcha_form") ||
-               body_lower.contains("please verify yourself") ||
-               body_lower.contains("verify yourself to continue") {
-                return CheckResult::NotFound;
-            }
-        }
-
         // Check for JavaScript redirects using lightweight pattern matching
         // This is much faster than a headless browser but can catch common patterns
-        if let Some(js_result) = self.check_js_redirects(&body_text, username, final_url.as_str()) {
+        if let Some(js_result) = self.check_js_redirects(&body_text, username, final_url.as_str(), evidence) {
             return js_result;
         }
         
@@ -673,6 +1093,7 @@ impl AccountChecker {
                body_lower.contains("/not-found") ||
                body_lower.contains("location.href.*404") ||
                body_lower.contains("window.location.*404") {
+                evidence.push(Evidence::not_found(EvidenceSource::JsRedirect, 0.6, "script references a 404 page"));
                 return CheckResult::NotFound;
             }
             // Check if redirecting away from username URL
@@ -697,6 +1118,7 @@ impl AccountChecker {
                                (redirect_target.contains("/404") || 
                                 redirect_target.contains("/not-found") ||
                                 redirect_target.contains("/error")) {
+                                evidence.push(Evidence::not_found(EvidenceSource::JsRedirect, 0.85, format!("script redirects to error page \"{}\"", redirect_target)));
                                 return CheckResult::NotFound;
                             }
                         }
@@ -713,12 +1135,17 @@ impl AccountChecker {
                 for cap in pattern.captures_iter(&body_text) {
                     if let Some(redirect_url) = cap.get(1) {
                         let redirect = redirect_url.as_str().to_lowercase();
-                        // TopCoder redirects to profiles.topcoder.com - check if username is in redirect
-                        if redirect.contains("profiles.topcoder.com") && !redirect.contains(&username_lower) {
+                        if let Some(pattern) = site.rules.redirect_away.iter().find(|s| redirect.contains(&s.to_lowercase())) {
+                            evidence.push(Evidence::not_found(EvidenceSource::SiteRules, 0.9, format!("meta refresh to \"{}\" (matches \"{}\")", redirect, pattern)));
+                            return CheckResult::NotFound;
+                        }
+                        if site.rules.refresh_needs_username && !redirect.contains(&username.to_lowercase()) {
+                            evidence.push(Evidence::not_found(EvidenceSource::SiteRules, 0.85, format!("meta refresh to \"{}\" drops the username", redirect)));
                             return CheckResult::NotFound;
                         }
                         // Generic 404 redirects
                         if redirect.contains("/404") || redirect.contains("/not-found") || redirect.contains("/error") {
+                            evidence.push(Evidence::not_found(EvidenceSource::MetaRefresh, 0.85, format!("meta refresh to error page \"{}\"", redirect)));
                             return CheckResult::NotFound;
                         }
                     }
@@ -726,53 +1153,33 @@ impl AccountChecker {
             }
             // Fallback: simple check for 404 in refresh
             if body_lower.contains("/404") || body_lower.contains("/not-found") {
+                evidence.push(Evidence::not_found(EvidenceSource::MetaRefresh, 0.6, "meta refresh page references a 404 page"));
                 return CheckResult::NotFound;
             }
         }
 
-        // Twitter/X: Special handling - returns 200 with SPA shell, username in URL path indicates account exists
-        // This check must happen before site-specific and SPA detection
-        if (url_lower.contains("twitter.com") || final_url_lower.contains("twitter.com") ||
-            url_lower.contains("x.com") || final_url_lower.contains("x.com")) &&
-           status.as_u16() == 200 {
-            // Twitter/X: If username is in the URL path, account exists
-            // Twitter/X is an SPA and doesn't always include username in initial HTML
-            if final_url_lower.contains(&format!("/{}", username_lower)) ||
-               url_lower.contains(&format!("/{}", username_lower)) {
-                return CheckResult::Found;
-            }
+        // A rate-limit response proves nothing either way, whatever the site's rules would make of it
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            evidence.push(Evidence::inconclusive(EvidenceSource::Status, "HTTP 429"));
+            return CheckResult::RateLimited { retry_after_secs: retry_after };
         }
-        
-        // Site-specific checks before general detection
+
+        // Site rules before general detection
         // These are more aggressive and site-aware
-        let site_specific_result = self.check_site_specific(url, &body_text, &body_lower, username, final_url.as_str(), status.as_u16());
-        if let Some(result) = site_specific_result {
+        if let Some(result) = self.check_rules(site, &body_lower, username, status.as_u16(), evidence) {
             return result;
         }
         
         // Check status code
         match status.as_u16() {
-            // eBay: 503 Service Unavailable often means user doesn't exist (blocked/not found)
+            // Sites where 503 means a missing user list it in their error_codes
             503 => {
-                if final_url_lower.contains("ebay.com") || url_lower.contains("ebay.com") {
-                    // eBay 503 for /usr/USERNAME typically means user doesn't exist
-                    return CheckResult::NotFound;
-                }
-                // For other sites, 503 is a server error
-                CheckResult::Error("Service temporarily unavailable (503)".to_string())
-            },
+                evidence.push(Evidence::inconclusive(EvidenceSource::Status, "HTTP 503"));
+                CheckResult::Unavailable("Service temporarily unavailable (503)".to_string())
+            }
             200 => {
                 // Even with 200 status, check if it's actually a 404 page
                 // Many sites return 200 with a 404 page content
-                // Special check for Wikipedia: redlink means page doesn't exist
-                if final_url_lower.contains("wikipedia.org") &&
-                   (body_lower.contains("page does not exist") || 
-                    body_lower.contains("redlink") ||
-                    body_lower.contains("\"wgArticleId\":0") ||
-                    body_lower.contains("\"wgCurRevisionId\":0")) {
-                    return CheckResult::NotFound;
-                }
-                
                 // Special check for sites that return 200 but with empty/placeholder content
                 // Check if page is suspiciously empty or has placeholder text
                 let body_len = body_lower.len();
@@ -788,7 +1195,7 @@ impl AccountChecker {
                     }
                 }
                 
-                let is_not_found = self.contains_not_found_message(&body_lower, false);
+                let is_not_found = self.contains_not_found_message(&body_lower, false, evidence);
                 
                 if is_not_found {
                     CheckResult::NotFound
@@ -813,15 +1220,7 @@ impl AccountChecker {
                     
                     let username_lower = username.to_lowercase();
                     
-                    // Twitter/X: Returns 200 with SPA shell, but username is in URL path, not always in initial HTML
-                    // Twitter/X accounts exist if URL path contains username (even if not in initial HTML)
-                    if (final_url_lower.contains("twitter.com") || url_lower.contains("twitter.com") ||
-                        final_url_lower.contains("x.com") || url_lower.contains("x.com")) &&
-                       final_url_lower.contains(&format!("/{}", username_lower)) {
-                        // Twitter/X: If username is in the URL path, account likely exists
-                        // Twitter/X doesn't always include username in initial HTML for SPAs
-                        CheckResult::Found
-                    } else if is_spa_shell && final_url == url {
+                    if is_spa_shell && final_url == url {
                         // For other SPAs: require username in title/meta tags for valid profiles
                         // Non-existent users in SPAs won't have username in SEO tags
                         // Extract title to check if username is there
@@ -847,16 +1246,27 @@ impl AccountChecker {
                         // If username not in HTML at all, or only in scripts (not meta/title), mark as not found
                         // Valid SPA profiles include username in meta tags for SEO
                         if !body_lower.contains(&username_lower) {
+                            evidence.push(Evidence::not_found(EvidenceSource::SpaShell, 0.6, "SPA shell never mentions the username"));
                             CheckResult::NotFound
                         } else if !has_username_in_title && !has_username_in_meta {
                             // Username exists but only in scripts/JSON, not in SEO tags - likely doesn't exist
                             // Exception: Some sites like Glitch might have username in scripts but still valid
                             // For now, mark as not found if no meta/title evidence
+                            evidence.push(Evidence::not_found(EvidenceSource::SpaShell, 0.5, "SPA shell mentions the username only in scripts"));
                             CheckResult::NotFound
                         } else {
+                            evidence.push(Evidence::found(EvidenceSource::SpaShell, 0.85, "SPA shell has the username in its title or meta tags"));
                             CheckResult::Found
                         }
+                    } else if is_spa_shell {
+                        // Redirected SPA shell without 404 markers - the weakest kind of hit
+                        evidence.push(Evidence::found(EvidenceSource::SpaShell, 0.4, "HTTP 200 SPA shell with no not-found markers"));
+                        CheckResult::Found
+                    } else if body_lower.contains(&username_lower) {
+                        evidence.push(Evidence::found(EvidenceSource::Status, 0.8, "HTTP 200 page mentions the username, no not-found markers"));
+                        CheckResult::Found
                     } else {
+                        evidence.push(Evidence::found(EvidenceSource::Status, 0.55, "HTTP 200 with no not-found markers"));
                         CheckResult::Found
                     }
                 }
@@ -867,93 +1277,71 @@ impl AccountChecker {
                 if final_url_lower.contains("/error") || 
                    final_url_lower.contains("404") ||
                    final_url_lower.contains("not-found") {
+                    evidence.push(Evidence::not_found(EvidenceSource::Redirect, 0.9, format!("HTTP {} to error page {}", status.as_u16(), final_url)));
                     return CheckResult::NotFound;
                 }
                 // Check if username disappeared from URL
                 if url_lower.contains(&username_lower) && !final_url_lower.contains(&username_lower) {
+                    evidence.push(Evidence::not_found(EvidenceSource::Redirect, 0.85, format!("HTTP {} to {}, which drops the username", status.as_u16(), final_url)));
                     return CheckResult::NotFound;
                 }
+                evidence.push(Evidence::found(EvidenceSource::Redirect, 0.6, format!("HTTP {} keeps the username", status.as_u16())));
                 CheckResult::Found
             }
-            404 => CheckResult::NotFound,
-             403 => {
+            404 => {
+                evidence.push(Evidence::not_found(EvidenceSource::Status, 0.95, "HTTP 404"));
+                CheckResult::NotFound
+            }
+            403 => {
                 // 403 might mean account exists but is private, or account doesn't exist, or anti-bot protection
-                // Twitter/X: Returns 403 for all requests due to anti-bot protection, but we can still check if username is in HTML
-                if url_lower.contains("twitter.com") || url_lower.contains("x.com") {
-                    // Twitter/X shows username in title or meta tags even with 403
-                    // Check if username appears in title or og:title - valid profiles have it even with 403
-                    let title_has_username = if let (Some(title_start), Some(title_end)) = 
-                        (body_lower.find("<title>"), body_lower.find("</title>")) {
-                        if title_start + 7 < title_end {
-                            body_lower[title_start + 7..title_end].contains(&username_lower)
-                        } else {
-                            false
-                        }
-                    } else {
-                        false
-                    };
-                    
-                    let username_in_og_title = (body_lower.contains("property=\"og:title\"") || 
-                                               body_lower.contains("property='og:title'")) &&
-                                              body_lower.contains(&username_lower);
-                    
-                    // If username is in title or og:title, account exists (even with 403 blocking)
-                    if title_has_username || username_in_og_title || body_lower.contains(&format!("/{}</", username_lower)) {
-                        CheckResult::Found
-                    } else {
-                        // No username found - might not exist, but Twitter blocks all requests so we can't be sure
-                        // Default to Found since 403 on Twitter usually means anti-bot, not missing account
-                        CheckResult::Found
-                    }
+                // Sites that 403 everyone (Twitter/X) list it in their found_codes
+                if self.contains_not_found_message(&body_lower, false, evidence) {
+                    CheckResult::NotFound
                 } else {
-                    // For other sites, check body for not found messages
-                    if self.contains_not_found_message(&body_lower, false) {
-                        CheckResult::NotFound
-                    } else {
-                        // Likely private/exists but blocked
-                        CheckResult::Found
-                    }
+                    // Likely private/exists but blocked
+                    evidence.push(Evidence::found(EvidenceSource::Status, 0.4, "HTTP 403 without not-found markers (private or blocked)"));
+                    CheckResult::Found
                 }
             }
             400 => {
                 // Bad request - might be invalid username format or requires auth
-                if self.contains_not_found_message(&body_lower, false) {
+                if self.contains_not_found_message(&body_lower, false, evidence) {
                     CheckResult::NotFound
                 } else {
-                    CheckResult::Error("HTTP 400 Bad Request (possibly requires authentication)".to_string())
+                    evidence.push(Evidence::inconclusive(EvidenceSource::Status, "HTTP 400"));
+                    CheckResult::InvalidUsername("HTTP 400 Bad Request (the site rejected the username)".to_string())
                 }
             }
-            429 => {
-                // Rate limited - return error but don't fail completely
-                CheckResult::Error("HTTP 429 Rate Limited (try again later)".to_string())
-            }
             520..=524 => {
                 // Cloudflare errors - site might be down
-                CheckResult::Error(format!("HTTP {} Cloudflare Error (site may be temporarily unavailable)", status))
+                evidence.push(Evidence::inconclusive(EvidenceSource::Status, format!("HTTP {}", status.as_u16())));
+                CheckResult::Unavailable(format!("HTTP {} Cloudflare Error (site may be temporarily unavailable)", status))
             }
             999 => {
                 // LinkedIn's anti-bot protection
-                CheckResult::Error("HTTP 999 Anti-bot protection (requires authentication)".to_string())
+                evidence.push(Evidence::inconclusive(EvidenceSource::Status, "HTTP 999"));
+                CheckResult::Challenge("HTTP 999 Anti-bot protection (requires authentication)".to_string())
             }
             _ => {
                 // Check body for not found messages even with other status codes
-                if self.contains_not_found_message(&body_lower, false) {
+                if self.contains_not_found_message(&body_lower, false, evidence) {
                     CheckResult::NotFound
                 } else if status.is_success() {
+                    evidence.push(Evidence::found(EvidenceSource::Status, 0.5, format!("HTTP {} with no not-found markers", status.as_u16())));
                     CheckResult::Found
+                } else if status.is_server_error() {
+                    evidence.push(Evidence::inconclusive(EvidenceSource::Status, format!("HTTP {}", status.as_u16())));
+                    CheckResult::Unavailable(format!("HTTP {}", status))
                 } else {
-                    // Handle DNS/SSL errors more gracefully
-                    if site_name == "MySpace" || site_name == "Ask.fm" {
-                        CheckResult::Error(format!("HTTP {} (site may be unavailable or requires SSL verification)", status))
-                    } else {
-                        CheckResult::Error(format!("HTTP {}", status))
-                    }
+                    evidence.push(Evidence::inconclusive(EvidenceSource::Status, format!("HTTP {}", status.as_u16())));
+                    CheckResult::Error(format!("HTTP {}", status))
                 }
             }
         }
     }
 
-    fn contains_not_found_message(&self, body: &str, is_spa: bool) -> bool {
+    /// Look for generic "not found" wording and 404 markers, recording what matched in `evidence`
+    fn contains_not_found_message(&self, body: &str, is_spa: bool, evidence: &mut Vec<Evidence>) -> bool {
         let body_lower = body.to_lowercase();
         let body_len = body.len();
         
@@ -1003,10 +1391,12 @@ impl AccountChecker {
             "wgCurRevisionId\":0",  // Wikipedia pattern
         ];
         
-        let has_explicit_user_not_found = explicit_user_patterns.iter()
-            .any(|pattern| body_lower.contains(pattern));
+        let explicit_match = explicit_user_patterns.iter()
+            .find(|pattern| body_lower.contains(*pattern));
+        let has_explicit_user_not_found = explicit_match.is_some();
         
-        if has_explicit_user_not_found {
+        if let Some(pattern) = explicit_match {
+            evidence.push(Evidence::not_found(EvidenceSource::NotFoundMessage, 0.9, format!("page says \"{}\"", pattern)));
             return true;
         }
         
@@ -1092,15 +1482,21 @@ impl AccountChecker {
         if (is_spa || is_spa_shell_detected) && !has_explicit_user_not_found && !has_prominent_404 && !has_404_image {
             // Don't mark as 404 if it's just an SPA shell without clear indicators
             // Only return true if we have VERY strong indicators (title tag, explicit messages, images)
+            if has_404_in_title {
+                evidence.push(Evidence::not_found(EvidenceSource::NotFoundMessage, 0.85, "page title says not found"));
+            }
             return has_404_in_title || has_explicit_user_not_found || has_404_image;
         }
         
         // Check for 404 in title OR prominent display OR with context
         if has_404_in_title {
+            evidence.push(Evidence::not_found(EvidenceSource::NotFoundMessage, 0.85, "page title says not found"));
             return true; // Title tags are very reliable
         }
         
         if has_prominent_404 || has_404_image {
+            let detail = if has_prominent_404 { "page prominently displays 404" } else { "page shows a 404 image" };
+            evidence.push(Evidence::not_found(EvidenceSource::NotFoundMessage, 0.75, detail));
             return true; // Prominent 404 or 404 images are reliable indicators
         }
         
@@ -1108,132 +1504,31 @@ impl AccountChecker {
         if has_404_with_context {
             // Very short pages are almost certainly 404 pages
             if body_len < 300 {
+                evidence.push(Evidence::not_found(EvidenceSource::NotFoundMessage, 0.75, format!("{} byte page mentions 404 with error wording", body_len)));
                 return true;
             }
             // Medium pages need additional evidence
             if body_len < 1000 && has_common_404_phrase {
+                evidence.push(Evidence::not_found(EvidenceSource::NotFoundMessage, 0.75, format!("{} byte page mentions 404 with a not-found phrase", body_len)));
                 return true;
             }
         }
         
         // Short pages with "not found" are likely 404 pages
         if body_len < 400 && (has_common_404_phrase || body_lower.contains("page not found")) {
+            evidence.push(Evidence::not_found(EvidenceSource::NotFoundMessage, 0.7, format!("{} byte page with a not-found phrase", body_len)));
             return true;
         }
         
         // Medium length pages need multiple strong indicators
         if body_len < 1200 && has_common_404_phrase && body_lower.contains("not found") && 
            (body_lower.contains("404") || has_404_in_title) {
+            evidence.push(Evidence::not_found(EvidenceSource::NotFoundMessage, 0.75, format!("{} byte page with several not-found markers", body_len)));
             return true;
         }
         
         false
     }
-
-    async fn check_discord_username(&self, username: &str) -> SiteResult {
-        // Discord uses user IDs in URLs, not usernames. 
-        // We'll try to check via Discord's API validation endpoint.
-        // Note: This is unreliable without authentication, but we'll attempt it.
-        
-        // Discord's username validation endpoint (used during registration)
-        let validation_url = "https://discord.com/api/v9/unique-username/username-attempt-unauthed";
-
-        let payload = serde_json::json!({
-            "username": username
-        });
-
-        match self.client
-            .post(validation_url)
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
-            .await
-        {
-            Ok(response) => {
-                let status = response.status();
-                match status.as_u16() {
-                    200 => {
-                        // Discord's API returns 200 for valid username format checks
-                        // The response body contains info about availability
-                        let body = response.text().await.unwrap_or_default().to_lowercase();
-                        
-                        // Check if username is taken/exists
-                        // Discord API typically returns "taken" or similar indicators
-                        if body.contains("\"taken\":true") || body.contains("username_taken") 
-                            || body.contains("\"available\":false") {
-                            SiteResult {
-                                site: "Discord".to_string(),
-                                url: format!("https://discord.com/users/{}", username),
-                                category: "Social".to_string(),
-                                result: CheckResult::Found,
-                            }
-                        } else if body.contains("\"taken\":false") || body.contains("\"available\":true") {
-                            // Username is available, so account doesn't exist
-                            SiteResult {
-                                site: "Discord".to_string(),
-                                url: format!("https://discord.com/users/{}", username),
-                                category: "Social".to_string(),
-                                result: CheckResult::NotFound,
-                            }
-                        } else {
-                            // Can't determine - Discord uses user IDs, not usernames in URLs
-                            // Without proper API authentication, we can't reliably check
-                            SiteResult {
-                                site: "Discord".to_string(),
-                                url: format!("https://discord.com/users/{}", username),
-                                category: "Social".to_string(),
-                                result: CheckResult::Error(
-                                    "Discord uses user IDs, not usernames in URLs. Cannot reliably check without authentication.".to_string()
-                                ),
-                            }
-                        }
-                    }
-                    400 | 422 => {
-                        // Invalid username format
-                        SiteResult {
-                            site: "Discord".to_string(),
-                            url: format!("https://discord.com/users/{}", username),
-                            category: "Social".to_string(),
-                            result: CheckResult::NotFound,
-                        }
-                    }
-                    401 | 403 => {
-                        // Rate limited or requires authentication
-                        SiteResult {
-                            site: "Discord".to_string(),
-                            url: format!("https://discord.com/users/{}", username),
-                            category: "Social".to_string(),
-                            result: CheckResult::Error(
-                                "Discord API requires authentication. Discord uses user IDs, not usernames in URLs.".to_string()
-                            ),
-                        }
-                    }
-                    _ => {
-                        SiteResult {
-                            site: "Discord".to_string(),
-                            url: format!("https://discord.com/users/{}", username),
-                            category: "Social".to_string(),
-                            result: CheckResult::Error(format!(
-                                "Discord API returned status: {} (Discord uses user IDs, not usernames in URLs)",
-                                status
-                            )),
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                SiteResult {
-                    site: "Discord".to_string(),
-                    url: format!("https://discord.com/users/{}", username),
-                    category: "Social".to_string(),
-                    result: CheckResult::Error(format!(
-                        "Unable to check Discord: {} (Discord uses user IDs, not usernames in URLs)",
-                        e
-                    )),
-                }
-            }
-        }
-    }
 }
 
 impl Default for AccountChecker {
@@ -1242,3 +1537,72 @@ impl Default for AccountChecker {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checker(retries: u32, retry_delay: Duration) -> AccountChecker {
+        AccountChecker::with_options(CheckerOptions { retries, retry_delay, ..CheckerOptions::default() }).unwrap()
+    }
+
+    #[test]
+    fn retry_delay_doubles_with_equal_jitter() {
+        let checker = checker(3, Duration::from_millis(100));
+        for _ in 0..200 {
+            for (attempt, backoff) in [(1, 100), (2, 200), (3, 400)] {
+                let delay = checker.retry_delay(attempt, &CheckResult::Timeout).unwrap();
+                let backoff = Duration::from_millis(backoff);
+                assert!(delay >= backoff / 2 && delay <= backoff, "attempt {}: {:?}", attempt, delay);
+            }
+        }
+        assert_eq!(checker.retry_delay(4, &CheckResult::Timeout), None);
+    }
+
+    #[test]
+    fn retry_delay_is_capped() {
+        let checker = checker(20, Duration::from_secs(1));
+        assert!(checker.retry_delay(20, &CheckResult::Timeout).unwrap() <= MAX_BACKOFF);
+    }
+
+    #[test]
+    fn retry_delay_honours_retry_after() {
+        let checker = checker(2, Duration::from_millis(100));
+        let limited = |secs| CheckResult::RateLimited { retry_after_secs: Some(secs) };
+        assert_eq!(checker.retry_delay(1, &limited(5)), Some(Duration::from_secs(5)));
+        // Waiting longer than that isn't worth it
+        assert_eq!(checker.retry_delay(1, &limited(MAX_RETRY_AFTER.as_secs() + 1)), None);
+        assert!(checker.retry_delay(1, &CheckResult::RateLimited { retry_after_secs: None }).is_some());
+    }
+
+    #[test]
+    fn only_transient_results_are_retried() {
+        let checker = checker(2, Duration::from_millis(100));
+        for result in [
+            CheckResult::Timeout,
+            CheckResult::RateLimited { retry_after_secs: None },
+            CheckResult::Unavailable("HTTP 502".to_string()),
+        ] {
+            assert!(checker.retry_delay(1, &result).is_some(), "{:?}", result);
+        }
+        for result in [
+            CheckResult::Found,
+            CheckResult::NotFound,
+            CheckResult::Error("HTTP 401 Unauthorized".to_string()),
+            CheckResult::Error("too many redirects".to_string()),
+            CheckResult::Blocked("login required".to_string()),
+            CheckResult::DnsError("no such host".to_string()),
+        ] {
+            assert_eq!(checker.retry_delay(1, &result), None, "{:?}", result);
+        }
+    }
+
+    #[test]
+    fn replays_are_never_retried() {
+        let checker = AccountChecker::with_options(CheckerOptions {
+            fixtures: Some(FixtureMode::Replay(std::env::temp_dir())),
+            ..CheckerOptions::default()
+        })
+        .unwrap();
+        assert_eq!(checker.retry_delay(1, &CheckResult::Timeout), None);
+    }
+}
@@ -0,0 +1,214 @@
+//! Comparing two scans (`vidocq diff`)
+
+use crate::checker::{CheckResult, SiteResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// Found now, not found (or not checked) before
+    Appeared,
+    /// Found before, not found now
+    Disappeared,
+    /// Any other change of status, e.g. found -> error or timeout -> not_found
+    Changed,
+}
+
+impl ChangeKind {
+    /// Stable machine-readable name, matching the serialized value
+    pub fn tag(&self) -> &'static str {
+        match self {
+            ChangeKind::Appeared => "appeared",
+            ChangeKind::Disappeared => "disappeared",
+            ChangeKind::Changed => "changed",
+        }
+    }
+}
+
+/// How one (username, site) pair differs between two scans
+#[derive(Debug, Clone, Serialize)]
+pub struct Change {
+    pub username: String,
+    pub site: String,
+    pub category: String,
+    pub url: String,
+    pub change: ChangeKind,
+    /// Status tag in the old scan; `None` when the site wasn't checked
+    pub old: Option<String>,
+    /// Status tag in the new scan; `None` when the site wasn't checked
+    pub new: Option<String>,
+}
+
+/// Every change between two sets of results, ordered by username then site.
+/// Pairs checked in only one of the scans are reported only if they were found there,
+/// so adding or removing catalogue entries doesn't drown the real changes.
+pub fn diff(old: &[SiteResult], new: &[SiteResult]) -> Vec<Change> {
+    let index = |results: &'_ [SiteResult]| -> HashMap<(String, String), SiteResult> {
+        results.iter().map(|r| ((r.username.clone(), r.site.clone()), r.clone())).collect()
+    };
+    let old = index(old);
+    let new = index(new);
+
+    let keys: BTreeSet<&(String, String)> = old.keys().chain(new.keys()).collect();
+
+    let mut changes = Vec::new();
+    for key in keys {
+        let before = old.get(key);
+        let after = new.get(key);
+        let found_before = before.is_some_and(|r| r.result == CheckResult::Found);
+        let found_after = after.is_some_and(|r| r.result == CheckResult::Found);
+
+        let kind = match (before, after) {
+            (Some(b), Some(a)) if b.result.tag() == a.result.tag() => continue,
+            (Some(b), Some(_)) if found_after && b.result == CheckResult::NotFound => ChangeKind::Appeared,
+            (Some(_), Some(a)) if found_before && a.result == CheckResult::NotFound => ChangeKind::Disappeared,
+            (Some(_), Some(_)) => ChangeKind::Changed,
+            (None, Some(_)) if found_after => ChangeKind::Appeared,
+            (Some(_), None) if found_before => ChangeKind::Changed,
+            _ => continue,
+        };
+
+        // Describe the pair from the newest result we have
+        let latest = after.or(before).expect("key comes from one of the scans");
+        changes.push(Change {
+            username: key.0.clone(),
+            site: key.1.clone(),
+            category: latest.category.clone(),
+            url: latest.url.clone(),
+            change: kind,
+            old: before.map(|r| r.result.tag().to_string()),
+            new: after.map(|r| r.result.tag().to_string()),
+        });
+    }
+    changes
+}
+
+/// Like [`diff`], but only where both sides reached a verdict. An inconclusive result in `new`
+/// (timeout, rate limit, challenge...) keeps the pair's verdict from `old`, so found -> timeout ->
+/// found is no change at all and found -> timeout -> not_found still reports the disappearance.
+/// Returns the changes and the results to compare the next scan against.
+pub fn diff_conclusive(old: &[SiteResult], new: &[SiteResult]) -> (Vec<Change>, Vec<SiteResult>) {
+    let verdicts: HashMap<(&str, &str), &SiteResult> = old
+        .iter()
+        .filter(|r| r.result.is_conclusive())
+        .map(|r| ((r.username.as_str(), r.site.as_str()), r))
+        .collect();
+    let settled: Vec<SiteResult> = new
+        .iter()
+        .map(|r| match verdicts.get(&(r.username.as_str(), r.site.as_str())) {
+            Some(verdict) if !r.result.is_conclusive() => (*verdict).clone(),
+            _ => r.clone(),
+        })
+        .collect();
+
+    let conclusive = |tag: &Option<String>| tag.as_deref().is_none_or(|tag| matches!(tag, "found" | "not_found"));
+    let changes = diff(old, &settled).into_iter().filter(|c| conclusive(&c.old) && conclusive(&c.new)).collect();
+    (changes, settled)
+}
+
+/// Per-username wrapper written by batch `--json`
+#[derive(Deserialize)]
+struct UsernameGroup {
+    results: Vec<SiteResult>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ResultsFile {
+    Flat(Vec<SiteResult>),
+    Grouped(Vec<UsernameGroup>),
+    /// `--avatars` output: `{"results": ..., "likely_same_owner": [...]}`
+    WithClusters { results: Box<ResultsFile> },
+}
+
+impl ResultsFile {
+    fn into_results(self) -> Vec<SiteResult> {
+        match self {
+            ResultsFile::Flat(results) => results,
+            ResultsFile::Grouped(groups) => groups.into_iter().flat_map(|g| g.results).collect(),
+            ResultsFile::WithClusters { results } => results.into_results(),
+        }
+    }
+}
+
+/// Read results saved with `--json` (single, batch or with `--avatars`) or `--ndjson`
+pub fn load_results(path: &Path) -> Result<Vec<SiteResult>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+
+    match serde_json::from_str::<ResultsFile>(&text) {
+        Ok(file) => return Ok(file.into_results()),
+        Err(_) if !text.trim_start().starts_with('[') => {}
+        Err(e) => return Err(format!("{} is not a vidocq JSON result file: {}", path.display(), e)),
+    }
+
+    // NDJSON: one SiteResult per line
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| format!("{} line {} is not a vidocq result: {}", path.display(), number + 1, e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(site: &str, result: CheckResult) -> SiteResult {
+        SiteResult {
+            username: "alice".to_string(),
+            seed: None,
+            site: site.to_string(),
+            url: format!("https://{}.test/alice", site),
+            category: "Test".to_string(),
+            result,
+            status_code: None,
+            final_url: None,
+            attempts: 1,
+            confidence: 1.0,
+            evidence: Vec::new(),
+            profile: None,
+        }
+    }
+
+    fn summary(changes: &[Change]) -> Vec<(&str, ChangeKind, Option<&str>, Option<&str>)> {
+        changes.iter().map(|c| (c.site.as_str(), c.change, c.old.as_deref(), c.new.as_deref())).collect()
+    }
+
+    #[test]
+    fn every_status_change_is_reported() {
+        let old = [result("a", CheckResult::NotFound), result("b", CheckResult::Found), result("c", CheckResult::Found), result("d", CheckResult::Timeout)];
+        let new = [result("a", CheckResult::Found), result("b", CheckResult::NotFound), result("c", CheckResult::Timeout), result("d", CheckResult::Timeout)];
+        assert_eq!(
+            summary(&diff(&old, &new)),
+            [
+                ("a", ChangeKind::Appeared, Some("not_found"), Some("found")),
+                ("b", ChangeKind::Disappeared, Some("found"), Some("not_found")),
+                ("c", ChangeKind::Changed, Some("found"), Some("timeout")),
+            ]
+        );
+    }
+
+    #[test]
+    fn inconclusive_results_keep_the_last_verdict() {
+        let old = [result("a", CheckResult::Found), result("b", CheckResult::Found), result("c", CheckResult::Error("boom".to_string()))];
+        let new = [
+            result("a", CheckResult::Timeout),
+            result("b", CheckResult::RateLimited { retry_after_secs: None }),
+            result("c", CheckResult::Found),
+        ];
+        let (changes, settled) = diff_conclusive(&old, &new);
+        assert!(changes.is_empty(), "{:?}", changes);
+        let tags: Vec<&str> = settled.iter().map(|r| r.result.tag()).collect();
+        assert_eq!(tags, ["found", "found", "found"]);
+
+        // found -> timeout -> not_found is still a disappearance
+        let new = [result("a", CheckResult::NotFound), result("b", CheckResult::Found), result("c", CheckResult::Found)];
+        let (changes, _) = diff_conclusive(&settled, &new);
+        assert_eq!(summary(&changes), [("a", ChangeKind::Disappeared, Some("found"), Some("not_found"))]);
+    }
+}
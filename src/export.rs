@@ -0,0 +1,158 @@
+//! Spreadsheet-friendly export of scan results and diffs (`--format csv|tsv`)
+
+use crate::checker::SiteResult;
+use crate::diff::Change;
+use std::io::{self, Write};
+
+/// Column headers, in order. Treat these as a public interface: append, never rename or reorder.
+pub const COLUMNS: [&str; 7] = ["username", "site", "category", "url", "result", "error", "seed"];
+
+/// Column headers of `vidocq diff` exports, under the same rules as [`COLUMNS`]
+pub const CHANGE_COLUMNS: [&str; 7] = ["username", "site", "category", "url", "change", "old", "new"];
+
+/// Write results as delimited text with a header row. Fields containing the delimiter,
+/// a double quote or a line break are quoted as RFC 4180 describes, and records end in CRLF.
+/// Fields a spreadsheet would read as a formula are prefixed with `'` so they stay text.
+pub fn write_delimited<W: Write>(mut out: W, results: &[&SiteResult], delimiter: char) -> io::Result<()> {
+    write_record(&mut out, COLUMNS.iter().copied(), delimiter)?;
+    for result in results {
+        // Conclusive results have no error; everything else explains itself
+        let error = if result.result.is_conclusive() { String::new() } else { result.result.to_string() };
+        let fields = [
+            result.username.as_str(),
+            result.site.as_str(),
+            result.category.as_str(),
+            result.url.as_str(),
+            result.result.tag(),
+            error.as_str(),
+            result.seed.as_deref().unwrap_or(""),
+        ];
+        write_record(&mut out, fields.into_iter(), delimiter)?;
+    }
+    out.flush()
+}
+
+/// Write diff changes as delimited text, quoted and escaped like [`write_delimited`].
+/// `old` and `new` are empty where the site wasn't checked.
+pub fn write_changes<W: Write>(mut out: W, changes: &[Change], delimiter: char) -> io::Result<()> {
+    write_record(&mut out, CHANGE_COLUMNS.iter().copied(), delimiter)?;
+    for change in changes {
+        let fields = [
+            change.username.as_str(),
+            change.site.as_str(),
+            change.category.as_str(),
+            change.url.as_str(),
+            change.change.tag(),
+            change.old.as_deref().unwrap_or(""),
+            change.new.as_deref().unwrap_or(""),
+        ];
+        write_record(&mut out, fields.into_iter(), delimiter)?;
+    }
+    out.flush()
+}
+
+fn write_record<'a, W: Write>(out: &mut W, fields: impl Iterator<Item = &'a str>, delimiter: char) -> io::Result<()> {
+    for (i, field) in fields.enumerate() {
+        if i > 0 {
+            write!(out, "{}", delimiter)?;
+        }
+        // Usernames and profile URLs come from the command line and the sites, so a cell like
+        // `=HYPERLINK(...)` must not run when the export is opened in a spreadsheet
+        let field = if field.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+            format!("'{}", field)
+        } else {
+            field.to_string()
+        };
+        if field.contains([delimiter, '"', '\r', '\n']) {
+            write!(out, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            write!(out, "{}", field)?;
+        }
+    }
+    write!(out, "\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::CheckResult;
+
+    fn result(username: &str, site: &str, result: CheckResult) -> SiteResult {
+        SiteResult {
+            username: username.to_string(),
+            seed: None,
+            site: site.to_string(),
+            url: format!("https://example.com/{}", username),
+            category: "Social".to_string(),
+            result,
+            status_code: None,
+            final_url: None,
+            attempts: 1,
+            confidence: 1.0,
+            evidence: Vec::new(),
+            profile: None,
+        }
+    }
+
+    fn export(results: &[SiteResult], delimiter: char) -> String {
+        let mut out = Vec::new();
+        write_delimited(&mut out, &results.iter().collect::<Vec<_>>(), delimiter).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn header_row_lists_the_columns() {
+        assert_eq!(export(&[], ','), "username,site,category,url,result,error,seed\r\n");
+        assert_eq!(export(&[], '\t'), "username\tsite\tcategory\turl\tresult\terror\tseed\r\n");
+    }
+
+    #[test]
+    fn records_end_in_crlf() {
+        let mut found = result("alice", "GitHub", CheckResult::Found);
+        found.seed = Some("al".to_string());
+        let out = export(&[found], ',');
+        assert_eq!(out.lines().nth(1), Some("alice,GitHub,Social,https://example.com/alice,found,,al"));
+        assert!(out.ends_with("found,,al\r\n"));
+    }
+
+    #[test]
+    fn fields_are_quoted_when_needed() {
+        let out = export(&[result("alice", "Site, Inc", CheckResult::Blocked("says \"no\"\r\nbye".to_string()))], ',');
+        let record = out.split_once("\r\n").unwrap().1;
+        assert!(record.starts_with("alice,\"Site, Inc\",Social,"), "{}", record);
+        assert!(record.contains(",\"Blocked: says \"\"no\"\"\r\nbye\","), "{}", record);
+
+        // Commas only need quoting when they are the delimiter
+        let out = export(&[result("alice", "Site, Inc", CheckResult::Found)], '\t');
+        assert!(out.contains("\r\nalice\tSite, Inc\tSocial\t"), "{:?}", out);
+    }
+
+    #[test]
+    fn changes_have_their_own_columns() {
+        let old = [result("alice", "GitHub", CheckResult::NotFound)];
+        let new = [result("alice", "GitHub", CheckResult::Found), result("=bob", "GitLab", CheckResult::Found)];
+        let mut out = Vec::new();
+        write_changes(&mut out, &crate::diff::diff(&old, &new), '\t').unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "username\tsite\tcategory\turl\tchange\told\tnew\r\n\
+             '=bob\tGitLab\tSocial\thttps://example.com/=bob\tappeared\t\tfound\r\n\
+             alice\tGitHub\tSocial\thttps://example.com/alice\tappeared\tnot_found\tfound\r\n"
+        );
+    }
+
+    #[test]
+    fn formulas_are_neutralised() {
+        for (username, escaped) in [("=cmd", "'=cmd"), ("+1", "'+1"), ("-1", "'-1"), ("@sum", "'@sum")] {
+            let out = export(&[result(username, "Site", CheckResult::Found)], ',');
+            assert!(out.split("\r\n").nth(1).unwrap().starts_with(&format!("{},", escaped)), "{}", out);
+        }
+        let out = export(&[result("\tx", "Site", CheckResult::Found)], ',');
+        assert!(out.contains("\r\n'\tx,"), "{:?}", out);
+        let out = export(&[result("\rx", "Site", CheckResult::Found)], ',');
+        assert!(out.contains("\r\n\"'\rx\","), "{:?}", out);
+        // A separator inside a name is harmless
+        let out = export(&[result("john-doe", "Site", CheckResult::Found)], ',');
+        assert!(out.contains("\r\njohn-doe,"), "{:?}", out);
+    }
+}
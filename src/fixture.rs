@@ -0,0 +1,137 @@
+//! Recorded HTTP responses for offline, repeatable checks (`--record`, `--replay`, `vidocq regress`)
+//!
+//! A fixture directory holds one JSON file per site and username, at
+//! `<dir>/<site>/<username>.json` (both names reduced to filesystem-safe characters):
+//!
+//! ```json
+//! {
+//!   "site": "GitHub",
+//!   "username": "torvalds",
+//!   "url": "https://github.com/torvalds",
+//!   "expect": "found",
+//!   "recorded_at": "Sat, 17 Oct 2026 09:12:44 GMT",
+//!   "response": {
+//!     "status": 200,
+//!     "headers": [["content-type", "text/html; charset=utf-8"]],
+//!     "final_url": "https://github.com/torvalds",
+//!     "redirects": [],
+//!     "body": "<!DOCTYPE html>..."
+//!   }
+//! }
+//! ```
+//!
+//! `expect` is the status tag the check produced when the fixture was recorded. Review it before
+//! committing a fixture: `vidocq regress` fails whenever a replayed check disagrees with it.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Where an `AccountChecker` gets its responses from, when not simply from the network
+#[derive(Debug, Clone)]
+pub enum FixtureMode {
+    /// Hit the network and save every response to this directory
+    Record(PathBuf),
+    /// Never touch the network; answer every request from the fixtures in this directory
+    Replay(PathBuf),
+}
+
+/// One HTTP exchange as the detection logic sees it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    /// Response headers in the order received; names are lowercase
+    pub headers: Vec<(String, String)>,
+    /// URL of the last response, after redirects
+    pub final_url: String,
+    /// Every response that redirected, in order
+    #[serde(default)]
+    pub redirects: Vec<Redirect>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Redirect {
+    pub url: String,
+    pub status: u16,
+    pub location: String,
+}
+
+impl RecordedResponse {
+    /// First value of a header, matched case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+}
+
+/// A recorded response for one site and username, with the outcome it is expected to produce
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fixture {
+    pub site: String,
+    pub username: String,
+    /// The URL that was requested
+    pub url: String,
+    /// Status tag the check should produce, e.g. `found` or `not_found`
+    pub expect: String,
+    /// HTTP date of the recording
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recorded_at: Option<String>,
+    pub response: RecordedResponse,
+}
+
+/// Reduce a name to characters safe in a file name on every platform
+fn file_safe(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect::<String>()
+        .trim_start_matches('.')
+        .to_string()
+}
+
+/// Where the fixture for a site and username lives
+pub fn path_for(dir: &Path, site: &str, username: &str) -> PathBuf {
+    dir.join(file_safe(site)).join(format!("{}.json", file_safe(username)))
+}
+
+pub fn load(path: &Path) -> Result<Fixture, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("cannot read fixture {}: {}", path.display(), e))?;
+    serde_json::from_str(&text).map_err(|e| format!("{} is not a vidocq fixture: {}", path.display(), e))
+}
+
+/// The recorded response for a site and username
+pub fn load_response(dir: &Path, site: &str, username: &str) -> Result<RecordedResponse, String> {
+    let path = path_for(dir, site, username);
+    if !path.exists() {
+        return Err(format!("no fixture for {} on {} (expected {})", username, site, path.display()));
+    }
+    let fixture = load(&path)?;
+    // Different names can reduce to the same file name
+    if fixture.site != site || fixture.username != username {
+        return Err(format!("{} records {} on {}, not {} on {}", path.display(), fixture.username, fixture.site, username, site));
+    }
+    Ok(fixture.response)
+}
+
+pub fn save(dir: &Path, fixture: &Fixture) -> Result<PathBuf, String> {
+    let path = path_for(dir, &fixture.site, &fixture.username);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("cannot create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(fixture).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json + "\n").map_err(|e| format!("cannot write fixture {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Every fixture under `dir`, sorted by path
+pub fn load_all(dir: &Path) -> Result<Vec<(PathBuf, Fixture)>, String> {
+    let read_dir = |dir: &Path| -> Result<Vec<PathBuf>, String> {
+        let entries = std::fs::read_dir(dir).map_err(|e| format!("cannot read fixture directory {}: {}", dir.display(), e))?;
+        entries.map(|entry| entry.map(|e| e.path()).map_err(|e| e.to_string())).collect()
+    };
+
+    let mut paths = Vec::new();
+    for site_dir in read_dir(dir)?.into_iter().filter(|path| path.is_dir()) {
+        paths.extend(read_dir(&site_dir)?.into_iter().filter(|path| path.extension().is_some_and(|ext| ext == "json")));
+    }
+    paths.sort();
+    paths.into_iter().map(|path| load(&path).map(|fixture| (path, fixture))).collect()
+}
@@ -0,0 +1,358 @@
+//! Scan history in a local SQLite database (`--db`, `vidocq history`)
+
+use crate::checker::SiteResult;
+use crate::pivot::Discovery;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS scans (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    started_at  INTEGER NOT NULL,  -- unix seconds
+    duration_ms INTEGER NOT NULL,
+    usernames   TEXT    NOT NULL,  -- JSON array, in input order
+    site_count  INTEGER NOT NULL,
+    options     TEXT    NOT NULL   -- JSON object of the scan's command-line options
+);
+CREATE TABLE IF NOT EXISTS results (
+    scan_id     INTEGER NOT NULL REFERENCES scans(id) ON DELETE CASCADE,
+    username    TEXT    NOT NULL,
+    site        TEXT    NOT NULL,
+    category    TEXT    NOT NULL,
+    url         TEXT    NOT NULL,
+    status      TEXT    NOT NULL,  -- CheckResult tag
+    status_code INTEGER,
+    final_url   TEXT,
+    confidence  REAL    NOT NULL,
+    result      TEXT    NOT NULL   -- the full SiteResult as JSON
+);
+CREATE TABLE IF NOT EXISTS discoveries (  -- --recurse links between accounts
+    scan_id       INTEGER NOT NULL REFERENCES scans(id) ON DELETE CASCADE,
+    from_username TEXT    NOT NULL,
+    from_site     TEXT    NOT NULL,
+    url           TEXT    NOT NULL,
+    site          TEXT    NOT NULL,
+    username      TEXT    NOT NULL,
+    depth         INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS results_scan ON results (scan_id);
+CREATE INDEX IF NOT EXISTS results_username_site ON results (username, site);
+";
+
+/// One recorded scan, without its results
+#[derive(Debug, Clone)]
+pub struct ScanRecord {
+    pub id: i64,
+    pub started: SystemTime,
+    pub duration: Duration,
+    pub usernames: Vec<String>,
+    pub site_count: usize,
+    pub options: serde_json::Value,
+    /// Number of results stored, and how many of them were found
+    pub total: usize,
+    pub found: usize,
+}
+
+/// A finished scan to record
+pub struct NewScan<'a> {
+    pub usernames: &'a [String],
+    pub started: SystemTime,
+    pub duration: Duration,
+    pub site_count: usize,
+    /// The scan's command-line options, as a JSON object
+    pub options: &'a serde_json::Value,
+    pub results: &'a [&'a SiteResult],
+    /// Links followed with `--recurse`
+    pub discoveries: &'a [Discovery],
+}
+
+#[derive(Debug)]
+pub enum HistoryError {
+    Sqlite(rusqlite::Error),
+    /// A stored row could not be decoded
+    Corrupt(String),
+    NoSuchScan(i64),
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryError::Sqlite(e) => write!(f, "history database error: {}", e),
+            HistoryError::Corrupt(message) => write!(f, "history database is corrupt: {}", message),
+            HistoryError::NoSuchScan(id) => write!(f, "no scan with id {} in the history", id),
+        }
+    }
+}
+
+impl std::error::Error for HistoryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HistoryError::Sqlite(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for HistoryError {
+    fn from(e: rusqlite::Error) -> Self {
+        HistoryError::Sqlite(e)
+    }
+}
+
+pub struct History {
+    conn: Connection,
+}
+
+impl History {
+    /// Open (creating if needed) the history database at `path`
+    pub fn open(path: &Path) -> Result<Self, HistoryError> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Store a finished scan with all its results and discoveries, in one transaction;
+    /// returns the new scan id
+    pub fn record_scan(&mut self, scan: &NewScan) -> Result<i64, HistoryError> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO scans (started_at, duration_ms, usernames, site_count, options) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                scan.started.duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0),
+                scan.duration.as_millis() as i64,
+                serde_json::to_string(scan.usernames).map_err(|e| HistoryError::Corrupt(e.to_string()))?,
+                scan.site_count as i64,
+                scan.options.to_string(),
+            ],
+        )?;
+        let scan_id = tx.last_insert_rowid();
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO results (scan_id, username, site, category, url, status, status_code, final_url, confidence, result)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?;
+            for result in scan.results {
+                insert.execute(params![
+                    scan_id,
+                    result.username,
+                    result.site,
+                    result.category,
+                    result.url,
+                    result.result.tag(),
+                    result.status_code,
+                    result.final_url,
+                    result.confidence as f64,
+                    serde_json::to_string(result).map_err(|e| HistoryError::Corrupt(e.to_string()))?,
+                ])?;
+            }
+
+            let mut insert = tx.prepare(
+                "INSERT INTO discoveries (scan_id, from_username, from_site, url, site, username, depth)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for discovery in scan.discoveries {
+                insert.execute(params![
+                    scan_id,
+                    discovery.from_username,
+                    discovery.from_site,
+                    discovery.url,
+                    discovery.site,
+                    discovery.username,
+                    discovery.depth,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(scan_id)
+    }
+
+    /// Every recorded scan, oldest first
+    pub fn list_scans(&self) -> Result<Vec<ScanRecord>, HistoryError> {
+        let mut query = self.conn.prepare(&format!("{} GROUP BY scans.id ORDER BY scans.id", SCAN_QUERY))?;
+        let rows = query.query_map([], scan_row)?;
+        rows.map(|row| row?).collect()
+    }
+
+    /// One scan and its results, in the order they were stored
+    pub fn load_scan(&self, id: i64) -> Result<(ScanRecord, Vec<SiteResult>), HistoryError> {
+        let record = self
+            .conn
+            .query_row(&format!("{} WHERE scans.id = ?1 GROUP BY scans.id", SCAN_QUERY), [id], scan_row)
+            .optional()?
+            .ok_or(HistoryError::NoSuchScan(id))??;
+
+        let mut query = self.conn.prepare("SELECT result FROM results WHERE scan_id = ?1 ORDER BY rowid")?;
+        let rows = query.query_map([id], |row| row.get::<_, String>(0))?;
+        let mut results = Vec::new();
+        for json in rows {
+            let result = serde_json::from_str(&json?)
+                .map_err(|e| HistoryError::Corrupt(format!("scan {}: {}", id, e)))?;
+            results.push(result);
+        }
+        Ok((record, results))
+    }
+
+    /// The links `--recurse` followed during a scan, in the order they were found
+    pub fn load_discoveries(&self, id: i64) -> Result<Vec<Discovery>, HistoryError> {
+        let mut query = self.conn.prepare(
+            "SELECT from_username, from_site, url, site, username, depth FROM discoveries WHERE scan_id = ?1 ORDER BY rowid",
+        )?;
+        let rows = query.query_map([id], |row| {
+            Ok(Discovery {
+                from_username: row.get(0)?,
+                from_site: row.get(1)?,
+                url: row.get(2)?,
+                site: row.get(3)?,
+                username: row.get(4)?,
+                depth: row.get(5)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// The most recent scan id, if any
+    pub fn latest_scan_id(&self) -> Result<Option<i64>, HistoryError> {
+        Ok(self.conn.query_row("SELECT MAX(id) FROM scans", [], |row| row.get(0))?)
+    }
+}
+
+const SCAN_QUERY: &str = "
+SELECT scans.id, scans.started_at, scans.duration_ms, scans.usernames, scans.site_count, scans.options,
+       COUNT(results.scan_id), COALESCE(SUM(results.status = 'found'), 0)
+FROM scans LEFT JOIN results ON results.scan_id = scans.id";
+
+/// Decode a `SCAN_QUERY` row. The outer `Result` is SQLite's, the inner one our JSON decoding.
+fn scan_row(row: &rusqlite::Row) -> rusqlite::Result<Result<ScanRecord, HistoryError>> {
+    let id: i64 = row.get(0)?;
+    let started_at: i64 = row.get(1)?;
+    let duration_ms: i64 = row.get(2)?;
+    let usernames: String = row.get(3)?;
+    let site_count: i64 = row.get(4)?;
+    let options: String = row.get(5)?;
+    let total: i64 = row.get(6)?;
+    let found: i64 = row.get(7)?;
+
+    let decode = || -> Result<ScanRecord, HistoryError> {
+        Ok(ScanRecord {
+            id,
+            started: UNIX_EPOCH + Duration::from_secs(started_at.max(0) as u64),
+            duration: Duration::from_millis(duration_ms.max(0) as u64),
+            usernames: serde_json::from_str(&usernames).map_err(|e| HistoryError::Corrupt(format!("scan {}: {}", id, e)))?,
+            site_count: site_count as usize,
+            options: serde_json::from_str(&options).map_err(|e| HistoryError::Corrupt(format!("scan {}: {}", id, e)))?,
+            total: total as usize,
+            found: found as usize,
+        })
+    };
+    Ok(decode())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::CheckResult;
+
+    fn result(username: &str, site: &str, result: CheckResult) -> SiteResult {
+        SiteResult {
+            username: username.to_string(),
+            seed: None,
+            site: site.to_string(),
+            url: format!("https://{}.example/{}", site.to_lowercase(), username),
+            category: "Social".to_string(),
+            result,
+            status_code: Some(200),
+            final_url: None,
+            attempts: 1,
+            confidence: 0.9,
+            evidence: Vec::new(),
+            profile: None,
+        }
+    }
+
+    fn discovery(username: &str, depth: u32) -> Discovery {
+        Discovery {
+            from_username: "alice".to_string(),
+            from_site: "Alpha".to_string(),
+            url: format!("https://beta.example/{}", username),
+            site: "Beta".to_string(),
+            username: username.to_string(),
+            depth,
+        }
+    }
+
+    fn record(history: &mut History, usernames: &[String], results: &[SiteResult], discoveries: &[Discovery]) -> i64 {
+        let options = serde_json::json!({ "timeout": 10 });
+        history
+            .record_scan(&NewScan {
+                usernames,
+                started: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+                duration: Duration::from_millis(1500),
+                site_count: 2,
+                options: &options,
+                results: &results.iter().collect::<Vec<_>>(),
+                discoveries,
+            })
+            .unwrap()
+    }
+
+    fn json(results: &[SiteResult]) -> String {
+        serde_json::to_string(results).unwrap()
+    }
+
+    #[test]
+    fn scans_round_trip() {
+        let mut history = History::open(Path::new(":memory:")).unwrap();
+        assert_eq!(history.latest_scan_id().unwrap(), None);
+        assert!(history.list_scans().unwrap().is_empty());
+
+        let usernames = vec!["alice".to_string()];
+        let first = vec![result("alice", "Alpha", CheckResult::Found), result("alice", "Beta", CheckResult::Timeout)];
+        let second = vec![result("alice", "Beta", CheckResult::NotFound), result("alice", "Alpha", CheckResult::Found)];
+        let first_id = record(&mut history, &usernames, &first, &[]);
+        let second_id = record(&mut history, &usernames, &second, &[]);
+        assert_eq!(history.latest_scan_id().unwrap(), Some(second_id));
+
+        let scans = history.list_scans().unwrap();
+        assert_eq!(scans.iter().map(|scan| scan.id).collect::<Vec<_>>(), [first_id, second_id]);
+        let scan = &scans[0];
+        assert_eq!(scan.usernames, usernames);
+        assert_eq!(scan.started, UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+        assert_eq!(scan.duration, Duration::from_millis(1500));
+        assert_eq!(scan.site_count, 2);
+        assert_eq!(scan.options, serde_json::json!({ "timeout": 10 }));
+        assert_eq!((scan.total, scan.found), (2, 1));
+
+        // Results come back whole and in the order they were stored
+        let (record, results) = history.load_scan(second_id).unwrap();
+        assert_eq!(record.id, second_id);
+        assert_eq!(json(&results), json(&second));
+        assert_eq!(json(&history.load_scan(first_id).unwrap().1), json(&first));
+
+        assert!(matches!(history.load_scan(42), Err(HistoryError::NoSuchScan(42))));
+    }
+
+    #[test]
+    fn scans_without_results_are_listed() {
+        let mut history = History::open(Path::new(":memory:")).unwrap();
+        let id = record(&mut history, &["alice".to_string(), "bob".to_string()], &[], &[]);
+        let scans = history.list_scans().unwrap();
+        assert_eq!((scans[0].id, scans[0].total, scans[0].found), (id, 0, 0));
+        assert_eq!(scans[0].usernames, ["alice", "bob"]);
+    }
+
+    #[test]
+    fn discoveries_are_kept_per_scan() {
+        let mut history = History::open(Path::new(":memory:")).unwrap();
+        let usernames = vec!["alice".to_string()];
+        let found = vec![result("alice", "Alpha", CheckResult::Found)];
+        let discoveries = vec![discovery("bob", 1), discovery("carol", 2)];
+        let with = record(&mut history, &usernames, &found, &discoveries);
+        let without = record(&mut history, &usernames, &found, &[]);
+
+        assert_eq!(history.load_discoveries(with).unwrap(), discoveries);
+        assert!(history.load_discoveries(without).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,595 @@
+//! Converting other projects' site lists into vidocq catalogues (`vidocq import`)
+//!
+//! Sherlock (`data.json`), WhatsMyName (`wmn-data.json`) and Maigret (`data.json`) each describe
+//! thousands of sites with their own detection vocabulary. Their entries are mapped onto
+//! [`Rules`] as closely as the rules allow; entries that need something a vidocq site can't
+//! express are reported rather than imported half-working.
+
+use crate::sites::{validate_sites, ManifestError, RequestSpec, Rules, Site};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
+
+/// Category for imported sites whose source doesn't give one
+const DEFAULT_CATEGORY: &str = "Imported";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceFormat {
+    Sherlock,
+    WhatsMyName,
+    Maigret,
+}
+
+impl SourceFormat {
+    /// Recognise a source file by its shape: WhatsMyName has a `sites` array, Maigret a `sites`
+    /// object, and Sherlock is a flat object of site entries
+    pub fn detect(data: &Value) -> Option<Self> {
+        match data.get("sites") {
+            Some(Value::Array(_)) => Some(SourceFormat::WhatsMyName),
+            Some(Value::Object(_)) => Some(SourceFormat::Maigret),
+            Some(_) => None,
+            None => data.as_object().map(|_| SourceFormat::Sherlock),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SourceFormat::Sherlock => "Sherlock",
+            SourceFormat::WhatsMyName => "WhatsMyName",
+            SourceFormat::Maigret => "Maigret",
+        }
+    }
+}
+
+impl std::str::FromStr for SourceFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sherlock" => Ok(SourceFormat::Sherlock),
+            "whatsmyname" | "wmn" => Ok(SourceFormat::WhatsMyName),
+            "maigret" => Ok(SourceFormat::Maigret),
+            _ => Err(format!("unknown import format `{}` (expected sherlock, whatsmyname or maigret)", s)),
+        }
+    }
+}
+
+/// A source entry and what happened to it
+#[derive(Debug, Clone)]
+pub struct EntryNote {
+    pub name: String,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Import {
+    pub sites: Vec<Site>,
+    /// Entries that could not be mapped, with the reason
+    pub skipped: Vec<EntryNote>,
+    /// Imported entries that lost something on the way, e.g. a regex the `regex` crate rejects
+    pub warnings: Vec<EntryNote>,
+}
+
+/// Convert a parsed source file into catalogue entries
+pub fn import(data: &Value, format: SourceFormat) -> Result<Import, String> {
+    let entries: Vec<(String, Result<Site, String>)> = match format {
+        SourceFormat::Sherlock => {
+            let sites = data.as_object().ok_or("a Sherlock file is a JSON object of sites")?;
+            sites
+                .iter()
+                .filter(|(name, _)| !name.starts_with('$'))
+                .map(|(name, entry)| (name.clone(), object(entry).and_then(|entry| from_sherlock(name, entry))))
+                .collect()
+        }
+        SourceFormat::WhatsMyName => {
+            let sites = data.get("sites").and_then(Value::as_array).ok_or("a WhatsMyName file has a `sites` array")?;
+            sites
+                .iter()
+                .map(|entry| {
+                    let name = entry.get("name").and_then(Value::as_str).unwrap_or("<unnamed>").to_string();
+                    let site = object(entry).and_then(|entry| from_whatsmyname(&name, entry));
+                    (name, site)
+                })
+                .collect()
+        }
+        SourceFormat::Maigret => {
+            let sites = data.get("sites").and_then(Value::as_object).ok_or("a Maigret file has a `sites` object")?;
+            let engines = data.get("engines").and_then(Value::as_object);
+            sites
+                .iter()
+                .map(|(name, entry)| {
+                    let site = object(entry).and_then(|entry| from_maigret(name, &with_engine(entry, engines)?));
+                    (name.clone(), site)
+                })
+                .collect()
+        }
+    };
+
+    let mut import = Import::default();
+    let mut seen = HashSet::new();
+    for (name, site) in entries {
+        let site = site.and_then(|site| finish(site, &mut import.warnings));
+        match site {
+            Ok(site) if !seen.insert(site.name.to_lowercase()) => {
+                import.skipped.push(EntryNote { name, message: "duplicate site name".to_string() });
+            }
+            Ok(site) => import.sites.push(site),
+            Err(message) => import.skipped.push(EntryNote { name, message }),
+        }
+    }
+    Ok(import)
+}
+
+fn from_sherlock(name: &str, entry: &Map<String, Value>) -> Result<Site, String> {
+    let url = string(entry.get("url")).ok_or("no url")?;
+    let request = RequestSpec {
+        // We read the body either way, so HEAD is just a GET
+        method: string(entry.get("request_method")).filter(|method| !method.eq_ignore_ascii_case("GET") && !method.eq_ignore_ascii_case("HEAD")),
+        url: string(entry.get("urlProbe")).filter(|probe| *probe != url),
+        headers: headers(entry.get("headers")),
+        json: entry.get("request_payload").cloned(),
+        ..RequestSpec::default()
+    };
+
+    let mut rules = Rules::default();
+    match string(entry.get("errorType")).as_deref() {
+        // Any 2xx means the account exists
+        Some("status_code") => {
+            rules.found_codes = vec![200];
+            rules.error_codes = codes(entry.get("errorCode"));
+            if rules.error_codes.is_empty() {
+                rules.error_codes = vec![404];
+            }
+            rules.found_codes.retain(|code| !rules.error_codes.contains(code));
+        }
+        Some("message") => {
+            rules.absence = strings(entry.get("errorMsg"));
+            if rules.absence.is_empty() {
+                return Err("errorType message without an errorMsg".to_string());
+            }
+        }
+        Some("response_url") => rules.redirect_away = redirect_target(entry.get("errorUrl"))?,
+        Some(other) => return Err(format!("unknown errorType \"{}\"", other)),
+        None => return Err("no errorType".to_string()),
+    }
+
+    Ok(Site {
+        name: name.to_string(),
+        url,
+        category: if entry.get("isNSFW").and_then(Value::as_bool) == Some(true) { "NSFW" } else { DEFAULT_CATEGORY }.to_string(),
+        username_pattern: string(entry.get("regexCheck")),
+        unsupported: None,
+        claimed: string(entry.get("username_claimed")),
+        unclaimed: string(entry.get("username_unclaimed")),
+        request,
+        rules,
+        rate_limit: None,
+        profile: Default::default(),
+    })
+}
+
+fn from_whatsmyname(name: &str, entry: &Map<String, Value>) -> Result<Site, String> {
+    if entry.get("valid").and_then(Value::as_bool) == Some(false) {
+        return Err("marked invalid upstream".to_string());
+    }
+    let check = string(entry.get("uri_check")).ok_or("no uri_check")?.replace("{account}", "{}");
+    let url = string(entry.get("uri_pretty")).map(|url| url.replace("{account}", "{}")).unwrap_or_else(|| check.clone());
+    let mut request = RequestSpec {
+        url: Some(check).filter(|check| *check != url),
+        headers: headers(entry.get("headers")).into_iter().map(|(name, value)| (name, value.replace("{account}", "{}"))).collect(),
+        ..RequestSpec::default()
+    };
+    if let Some(body) = string(entry.get("post_body")).map(|body| body.replace("{account}", "{}")) {
+        request.method = Some("POST".to_string());
+        match serde_json::from_str::<Value>(&body) {
+            Ok(json) => request.json = Some(json),
+            Err(_) => request.form = form_fields(&body).ok_or("post_body is neither JSON nor a form")?,
+        }
+    }
+
+    // Found means e_code and e_string; missing means m_code and m_string
+    let e_code = entry.get("e_code").and_then(Value::as_u64);
+    let m_code = entry.get("m_code").and_then(Value::as_u64);
+    let mut rules = Rules {
+        presence: strings(entry.get("e_string")).into_iter().map(|s| s.replace("{account}", "{}")).collect(),
+        absence: strings(entry.get("m_string")),
+        ..Rules::default()
+    };
+    if e_code != m_code {
+        rules.error_codes = m_code.and_then(|code| u16::try_from(code).ok()).into_iter().collect();
+        // Without a string to look for, the status code is the whole signal
+        if rules.presence.is_empty() {
+            rules.found_codes = e_code.and_then(|code| u16::try_from(code).ok()).into_iter().collect();
+        }
+    }
+    if rules.presence.is_empty() && rules.found_codes.is_empty() && rules.absence.is_empty() && rules.error_codes.is_empty() {
+        return Err("no usable e_code/e_string or m_code/m_string".to_string());
+    }
+
+    let known = strings(entry.get("known"));
+    Ok(Site {
+        name: name.to_string(),
+        url,
+        category: string(entry.get("cat")).map(|cat| title_case(&cat)).unwrap_or_else(|| DEFAULT_CATEGORY.to_string()),
+        username_pattern: None,
+        unsupported: None,
+        claimed: known.into_iter().next(),
+        unclaimed: None,
+        request,
+        rules,
+        rate_limit: None,
+        profile: Default::default(),
+    })
+}
+
+fn from_maigret(name: &str, entry: &Map<String, Value>) -> Result<Site, String> {
+    if entry.get("disabled").and_then(Value::as_bool) == Some(true) {
+        return Err("disabled upstream".to_string());
+    }
+    if let Some(kind) = string(entry.get("type")).filter(|kind| kind != "username") {
+        return Err(format!("checks a {} rather than a username", kind));
+    }
+    let url_main = string(entry.get("urlMain")).unwrap_or_default();
+    let url_subpath = string(entry.get("urlSubpath")).unwrap_or_default();
+    let expand = |url: String| url.replace("{urlMain}", &url_main).replace("{urlSubpath}", &url_subpath).replace("{username}", "{}");
+    let url = expand(string(entry.get("url")).ok_or("no url")?);
+    let request = RequestSpec {
+        url: string(entry.get("urlProbe")).map(expand).filter(|probe| *probe != url),
+        headers: headers(entry.get("headers")),
+        ..RequestSpec::default()
+    };
+
+    let mut rules = Rules::default();
+    match string(entry.get("checkType")).as_deref() {
+        Some("status_code") => rules.found_codes = vec![200],
+        Some("message") => {
+            rules.presence = strings(entry.get("presenseStrs"));
+            rules.absence = strings(entry.get("absenceStrs"));
+            if rules.presence.is_empty() && rules.absence.is_empty() {
+                return Err("checkType message without presenseStrs or absenceStrs".to_string());
+            }
+        }
+        Some("response_url") => rules.redirect_away = redirect_target(entry.get("errorUrl"))?,
+        Some(other) => return Err(format!("unknown checkType \"{}\"", other)),
+        None => return Err("no checkType".to_string()),
+    }
+
+    // Tags mix topics with two-letter country codes
+    let category = strings(entry.get("tags")).into_iter().find(|tag| tag.len() > 2).map(|tag| title_case(&tag));
+    Ok(Site {
+        name: name.to_string(),
+        url,
+        category: category.unwrap_or_else(|| DEFAULT_CATEGORY.to_string()),
+        username_pattern: string(entry.get("regexCheck")),
+        unsupported: None,
+        claimed: string(entry.get("usernameClaimed")),
+        unclaimed: string(entry.get("usernameUnclaimed")),
+        request,
+        rules,
+        rate_limit: None,
+        profile: Default::default(),
+    })
+}
+
+/// A Maigret entry with the defaults of its `engine` (XenForo, phpBB, ...) filled in
+fn with_engine(entry: &Map<String, Value>, engines: Option<&Map<String, Value>>) -> Result<Map<String, Value>, String> {
+    let Some(engine) = string(entry.get("engine")) else {
+        return Ok(entry.clone());
+    };
+    let defaults = engines
+        .and_then(|engines| engines.get(&engine))
+        .and_then(|engine| engine.get("site"))
+        .and_then(Value::as_object)
+        .ok_or_else(|| format!("uses engine \"{}\", which the file doesn't define", engine))?;
+    let mut merged = defaults.clone();
+    merged.extend(entry.iter().map(|(key, value)| (key.clone(), value.clone())));
+    Ok(merged)
+}
+
+/// Drop what the `regex` crate or the validator rejects where the rest of the entry still works,
+/// then validate the entry as a whole
+fn finish(mut site: Site, warnings: &mut Vec<EntryNote>) -> Result<Site, String> {
+    let name = site.name.clone();
+    let mut warn = |message: String| warnings.push(EntryNote { name: name.clone(), message });
+    if let Some(pattern) = site.username_pattern.take() {
+        match regex::Regex::new(&pattern) {
+            Ok(_) => site.username_pattern = Some(pattern),
+            Err(_) => warn(format!("dropped username_pattern the regex crate can't compile: {}", pattern)),
+        }
+    }
+    let pattern = site.username_pattern.as_deref().and_then(|pattern| regex::Regex::new(pattern).ok());
+    for (field, sample) in [("claimed", &mut site.claimed), ("unclaimed", &mut site.unclaimed)] {
+        if sample.as_ref().is_some_and(|s| s.trim().is_empty() || pattern.as_ref().is_some_and(|p| !p.is_match(s))) {
+            warn(format!("dropped {} sample \"{}\" that doesn't fit the username pattern", field, sample.take().unwrap_or_default()));
+        }
+    }
+    if site.claimed.is_some() && site.claimed == site.unclaimed {
+        site.unclaimed = None;
+    }
+
+    match validate_sites(std::slice::from_ref(&site)) {
+        Ok(()) => Ok(site),
+        Err(ManifestError::Invalid(problems)) => Err(problems.into_iter().map(|(_, _, problem)| problem).collect::<Vec<_>>().join("; ")),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// `errorUrl` as a `redirect_away` match. A bare home page can't be told apart from the profile
+/// URL by substring, so those are left to the checker's generic "redirect drops the username" test.
+fn redirect_target(error_url: Option<&Value>) -> Result<Vec<String>, String> {
+    let Some(error_url) = string(error_url) else {
+        return Ok(Vec::new());
+    };
+    if error_url.contains('{') {
+        return Err("errorUrl depends on the username".to_string());
+    }
+    let path = error_url.split_once("://").map_or(error_url.as_str(), |(_, rest)| rest).split_once('/').map_or("", |(_, path)| path);
+    Ok(if path.is_empty() { Vec::new() } else { vec![error_url] })
+}
+
+fn object(value: &Value) -> Result<&Map<String, Value>, String> {
+    value.as_object().ok_or_else(|| "entry is not a JSON object".to_string())
+}
+
+fn string(value: Option<&Value>) -> Option<String> {
+    value.and_then(Value::as_str).filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+/// A string or an array of strings
+fn strings(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::String(s)) if !s.is_empty() => vec![s.clone()],
+        Some(Value::Array(items)) => items.iter().filter_map(|item| string(Some(item))).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Header names and values; values that aren't strings are dropped
+fn headers(value: Option<&Value>) -> BTreeMap<String, String> {
+    let Some(headers) = value.and_then(Value::as_object) else {
+        return BTreeMap::new();
+    };
+    headers.iter().filter_map(|(name, value)| Some((name.clone(), value.as_str()?.to_string()))).collect()
+}
+
+/// `a=1&b={}` as form fields; the values are used as written, without percent-decoding
+fn form_fields(body: &str) -> Option<BTreeMap<String, String>> {
+    body.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| pair.split_once('=').map(|(name, value)| (name.to_string(), value.to_string())))
+        .collect::<Option<BTreeMap<_, _>>>()
+        .filter(|fields| !fields.is_empty())
+}
+
+/// A status code or an array of them
+fn codes(value: Option<&Value>) -> Vec<u16> {
+    match value {
+        Some(Value::Array(items)) => items.iter().filter_map(Value::as_u64).filter_map(|code| u16::try_from(code).ok()).collect(),
+        Some(value) => value.as_u64().and_then(|code| u16::try_from(code).ok()).into_iter().collect(),
+        None => Vec::new(),
+    }
+}
+
+fn title_case(text: &str) -> String {
+    let mut chars = text.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn run(data: Value) -> Import {
+        let format = SourceFormat::detect(&data).expect("format is recognised");
+        import(&data, format).unwrap()
+    }
+
+    fn rules(site: &Site) -> Value {
+        serde_json::to_value(&site.rules).unwrap()
+    }
+
+    fn notes(notes: &[EntryNote]) -> Vec<(&str, &str)> {
+        notes.iter().map(|note| (note.name.as_str(), note.message.as_str())).collect()
+    }
+
+    #[test]
+    fn formats_are_detected_by_shape() {
+        assert_eq!(SourceFormat::detect(&json!({ "GitHub": {} })), Some(SourceFormat::Sherlock));
+        assert_eq!(SourceFormat::detect(&json!({ "sites": [] })), Some(SourceFormat::WhatsMyName));
+        assert_eq!(SourceFormat::detect(&json!({ "sites": {}, "engines": {} })), Some(SourceFormat::Maigret));
+        assert_eq!(SourceFormat::detect(&json!({ "sites": 1 })), None);
+        assert_eq!(SourceFormat::detect(&json!([])), None);
+        assert_eq!("wmn".parse::<SourceFormat>(), Ok(SourceFormat::WhatsMyName));
+        assert!("spiderfoot".parse::<SourceFormat>().is_err());
+    }
+
+    #[test]
+    fn sherlock_error_types_map_to_rules() {
+        let import = run(json!({
+            "$schema": "data.schema.json",
+            "Codes": { "url": "https://codes.example/{}", "errorType": "status_code", "errorCode": [404, 410], "username_claimed": "blue" },
+            "Default": { "url": "https://default.example/{}", "errorType": "status_code" },
+            "Message": { "url": "https://message.example/{}", "errorType": "message", "errorMsg": ["No such user", "Gone"], "isNSFW": true },
+            "Redirect": { "url": "https://redirect.example/{}", "errorType": "response_url", "errorUrl": "https://redirect.example/login?next=home" },
+            "Home": { "url": "https://home.example/{}", "errorType": "response_url", "errorUrl": "https://home.example/" },
+            "Api": {
+                "url": "https://api.example/{}", "urlProbe": "https://api.example/v1/check", "errorType": "message", "errorMsg": "free",
+                "request_method": "POST", "request_payload": { "name": "{}" }, "headers": { "X-Client": "web" }
+            },
+            "Head": { "url": "https://head.example/{}", "errorType": "status_code", "request_method": "HEAD" },
+            "Unknown": { "url": "https://unknown.example/{}", "errorType": "captcha" },
+            "Untyped": { "url": "https://untyped.example/{}" },
+            "Messageless": { "url": "https://messageless.example/{}", "errorType": "message" },
+            "Personal": { "url": "https://personal.example/{}", "errorType": "response_url", "errorUrl": "https://personal.example/{}/missing" }
+        }));
+
+        let site = |name: &str| import.sites.iter().find(|site| site.name == name).unwrap_or_else(|| panic!("{} was not imported", name));
+        assert_eq!(rules(site("Codes")), json!({ "error_codes": [404, 410], "found_codes": [200] }));
+        assert_eq!(site("Codes").claimed.as_deref(), Some("blue"));
+        assert_eq!(rules(site("Default")), json!({ "error_codes": [404], "found_codes": [200] }));
+        assert_eq!(rules(site("Message")), json!({ "absence": ["No such user", "Gone"] }));
+        assert_eq!(site("Message").category, "NSFW");
+        assert_eq!(site("Codes").category, DEFAULT_CATEGORY);
+        assert_eq!(rules(site("Redirect")), json!({ "redirect_away": ["https://redirect.example/login?next=home"] }));
+        // A bare home page is left to the generic redirect checks
+        assert_eq!(rules(site("Home")), json!({}));
+
+        let api = site("Api");
+        assert_eq!(
+            serde_json::to_value(&api.request).unwrap(),
+            json!({ "method": "POST", "url": "https://api.example/v1/check", "headers": { "X-Client": "web" }, "json": { "name": "{}" } })
+        );
+        assert!(site("Head").request.is_empty());
+
+        assert_eq!(
+            notes(&import.skipped),
+            [
+                ("Messageless", "errorType message without an errorMsg"),
+                ("Personal", "errorUrl depends on the username"),
+                ("Unknown", "unknown errorType \"captcha\""),
+                ("Untyped", "no errorType"),
+            ]
+        );
+        assert!(import.warnings.is_empty());
+    }
+
+    #[test]
+    fn whatsmyname_codes_and_strings_map_to_rules() {
+        let import = run(json!({ "sites": [
+            {
+                "name": "Both", "uri_check": "https://both.example/api/{account}", "uri_pretty": "https://both.example/{account}",
+                "e_code": 200, "e_string": "\"user\":\"{account}\"", "m_code": 404, "m_string": "not found",
+                "known": ["alice", "bob"], "cat": "social"
+            },
+            { "name": "Codes", "uri_check": "https://codes.example/{account}", "e_code": 200, "m_code": 404 },
+            { "name": "Strings", "uri_check": "https://strings.example/{account}", "e_code": 200, "e_string": "Profile", "m_code": 200, "m_string": "Nobody" },
+            {
+                "name": "Post", "uri_check": "https://post.example/check", "uri_pretty": "https://post.example/u/{account}",
+                "post_body": "{\"username\":\"{account}\"}", "e_code": 200, "e_string": "taken", "m_code": 200, "m_string": "available"
+            },
+            {
+                "name": "Form", "uri_check": "https://form.example/check", "uri_pretty": "https://form.example/{account}",
+                "post_body": "user={account}&x=1", "e_code": 200, "e_string": "taken", "m_code": 200
+            },
+            { "name": "Retired", "uri_check": "https://retired.example/{account}", "e_code": 200, "m_code": 404, "valid": false },
+            { "name": "Blind", "uri_check": "https://blind.example/{account}", "e_code": 200, "m_code": 200 },
+            { "name": "Garbled", "uri_check": "https://garbled.example/check", "post_body": "not a form", "e_code": 200, "m_code": 404 }
+        ]}));
+
+        let site = |name: &str| import.sites.iter().find(|site| site.name == name).unwrap_or_else(|| panic!("{} was not imported", name));
+        let both = site("Both");
+        assert_eq!(both.url, "https://both.example/{}");
+        assert_eq!(both.request.url.as_deref(), Some("https://both.example/api/{}"));
+        assert_eq!(both.category, "Social");
+        assert_eq!(both.claimed.as_deref(), Some("alice"));
+        // With a presence string, e_code alone doesn't prove the account
+        assert_eq!(rules(both), json!({ "error_codes": [404], "absence": ["not found"], "presence": ["\"user\":\"{}\""] }));
+        assert_eq!(rules(site("Codes")), json!({ "error_codes": [404], "found_codes": [200] }));
+        assert_eq!(rules(site("Strings")), json!({ "absence": ["Nobody"], "presence": ["Profile"] }));
+
+        let post = site("Post");
+        assert_eq!(post.request.method.as_deref(), Some("POST"));
+        assert_eq!(post.request.json, Some(json!({ "username": "{}" })));
+        let form = site("Form");
+        assert_eq!(form.request.form, BTreeMap::from([("user".to_string(), "{}".to_string()), ("x".to_string(), "1".to_string())]));
+        assert_eq!(form.request.url.as_deref(), Some("https://form.example/check"));
+
+        assert_eq!(
+            notes(&import.skipped),
+            [
+                ("Retired", "marked invalid upstream"),
+                ("Blind", "no usable e_code/e_string or m_code/m_string"),
+                ("Garbled", "post_body is neither JSON nor a form"),
+            ]
+        );
+    }
+
+    #[test]
+    fn maigret_engines_are_merged_and_urls_expanded() {
+        let import = run(json!({
+            "engines": {
+                "XenForo": { "site": { "checkType": "message", "absenceStrs": ["The requested page could not be found"], "url": "{urlMain}{urlSubpath}/members/?username={username}" } }
+            },
+            "sites": {
+                "Forum": { "engine": "XenForo", "urlMain": "https://forum.example", "urlSubpath": "/community", "tags": ["ru", "forum"], "usernameClaimed": "admin" },
+                "Override": { "engine": "XenForo", "urlMain": "https://override.example", "checkType": "status_code" },
+                "Plain": { "url": "https://plain.example/@{username}", "checkType": "message", "presenseStrs": ["followers"], "tags": ["us"] },
+                "Orphan": { "engine": "vBulletin", "urlMain": "https://orphan.example" },
+                "Off": { "url": "https://off.example/{username}", "checkType": "status_code", "disabled": true },
+                "Mail": { "url": "https://mail.example/{username}", "checkType": "status_code", "type": "email" },
+                "Quiet": { "url": "https://quiet.example/{username}", "checkType": "message" }
+            }
+        }));
+
+        let site = |name: &str| import.sites.iter().find(|site| site.name == name).unwrap_or_else(|| panic!("{} was not imported", name));
+        let forum = site("Forum");
+        assert_eq!(forum.url, "https://forum.example/community/members/?username={}");
+        assert_eq!(rules(forum), json!({ "absence": ["The requested page could not be found"] }));
+        // Two-letter tags are countries, not categories
+        assert_eq!(forum.category, "Forum");
+        assert_eq!(forum.claimed.as_deref(), Some("admin"));
+        // The entry's own fields win over the engine's
+        assert_eq!(rules(site("Override")), json!({ "found_codes": [200] }));
+        assert_eq!(site("Override").url, "https://override.example/members/?username={}");
+        assert_eq!(rules(site("Plain")), json!({ "presence": ["followers"] }));
+        assert_eq!(site("Plain").category, DEFAULT_CATEGORY);
+
+        assert_eq!(
+            notes(&import.skipped),
+            [
+                ("Mail", "checks a email rather than a username"),
+                ("Off", "disabled upstream"),
+                ("Orphan", "uses engine \"vBulletin\", which the file doesn't define"),
+                ("Quiet", "checkType message without presenseStrs or absenceStrs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn redirect_targets_need_a_fixed_path() {
+        let target = |url: &str| redirect_target(Some(&json!(url)));
+        assert_eq!(target("https://example.com/404"), Ok(vec!["https://example.com/404".to_string()]));
+        assert_eq!(target("https://example.com/"), Ok(Vec::new()));
+        assert_eq!(target("https://example.com"), Ok(Vec::new()));
+        assert_eq!(target("https://example.com/{}"), Err("errorUrl depends on the username".to_string()));
+        assert_eq!(redirect_target(None), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn finish_drops_what_does_not_fit() {
+        let import = run(json!({
+            "Lookahead": { "url": "https://lookahead.example/{}", "errorType": "status_code", "regexCheck": "^(?!_)[a-z_]+$", "username_claimed": "alice" },
+            "Samples": {
+                "url": "https://samples.example/{}", "errorType": "status_code", "regexCheck": "^[a-z]+$",
+                "username_claimed": "Alice_1", "username_unclaimed": "nobody"
+            },
+            "Same": { "url": "https://same.example/{}", "errorType": "status_code", "username_claimed": "x", "username_unclaimed": "x" },
+            "Broken": { "url": "ftp://broken.example/{}", "errorType": "status_code" },
+            "broken": { "url": "https://broken.example/{}", "errorType": "status_code" },
+            "BROKEN": { "url": "https://other.example/{}", "errorType": "status_code" }
+        }));
+
+        let site = |name: &str| import.sites.iter().find(|site| site.name == name).unwrap_or_else(|| panic!("{} was not imported", name));
+        assert_eq!(site("Lookahead").username_pattern, None);
+        assert_eq!(site("Lookahead").claimed.as_deref(), Some("alice"));
+        assert_eq!(site("Samples").claimed, None);
+        assert_eq!(site("Samples").unclaimed.as_deref(), Some("nobody"));
+        assert_eq!(site("Same").claimed.as_deref(), Some("x"));
+        assert_eq!(site("Same").unclaimed, None);
+        assert_eq!(
+            notes(&import.warnings),
+            [
+                ("Lookahead", "dropped username_pattern the regex crate can't compile: ^(?!_)[a-z_]+$"),
+                ("Samples", "dropped claimed sample \"Alice_1\" that doesn't fit the username pattern"),
+            ]
+        );
+
+        // An entry the validator rejects is skipped whole, and names are unique case-insensitively
+        assert!(import.sites.iter().any(|site| site.name == "BROKEN"));
+        assert_eq!(import.skipped.len(), 2, "{:?}", import.skipped);
+        assert_eq!(import.skipped[0].name, "Broken");
+        assert_eq!(import.skipped[0].message, "url must start with http:// or https:// (got \"ftp://broken.example/{}\")");
+        assert_eq!(import.skipped[1].name, "broken");
+        assert_eq!(import.skipped[1].message, "duplicate site name");
+    }
+}
@@ -0,0 +1,80 @@
+//! Vidocq's username search engine, usable without the command-line front end.
+//!
+//! ```no_run
+//! use futures::StreamExt;
+//! use vidocq::{get_sites, scan, ScanOptions};
+//!
+//! # async fn run() -> Result<(), reqwest::Error> {
+//! let sites = get_sites();
+//! let mut results = Box::pin(scan("johndoe", &sites, ScanOptions::default())?);
+//! while let Some(result) = results.next().await {
+//!     println!("{}: {}", result.site, result.result);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod avatar;
+pub mod checker;
+pub mod diff;
+pub mod export;
+pub mod fixture;
+pub mod history;
+pub mod import;
+pub mod notify;
+pub mod permute;
+pub mod pivot;
+pub mod profile;
+pub mod report;
+pub mod selftest;
+pub mod server;
+mod scheduler;
+pub mod sites;
+
+pub use checker::{AccountChecker, CheckResult, CheckerOptions, ProxyMode, SiteResult};
+pub use sites::{get_sites, load_sites, Site};
+
+use futures::stream::{self, Stream, StreamExt};
+use std::sync::Arc;
+
+/// Options for [`scan`]
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Maximum number of sites checked at once
+    pub concurrency: usize,
+    /// Retries, per-host limits and proxies
+    pub checker: CheckerOptions,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self { concurrency: 20, checker: CheckerOptions::default() }
+    }
+}
+
+/// Check one username against every site, yielding each result as soon as it is known
+/// (so not in catalogue order). Fails only if the HTTP clients can't be built, e.g. a bad proxy URL.
+pub fn scan(username: &str, sites: &[Site], options: ScanOptions) -> Result<impl Stream<Item = SiteResult> + Send + 'static, reqwest::Error> {
+    let checker = Arc::new(AccountChecker::with_options(options.checker)?);
+    Ok(scan_with(checker, vec![username.to_string()], sites.to_vec(), options.concurrency))
+}
+
+/// Check every username against every site through one checker, sharing its concurrency,
+/// per-host limits and proxies. Results are yielded as they finish.
+pub fn scan_with(
+    checker: Arc<AccountChecker>,
+    usernames: Vec<String>,
+    sites: Vec<Site>,
+    concurrency: usize,
+) -> impl Stream<Item = SiteResult> + Send + 'static {
+    let jobs: Vec<(String, Site)> = usernames
+        .iter()
+        .flat_map(|username| sites.iter().map(move |site| (username.clone(), site.clone())))
+        .collect();
+    stream::iter(jobs)
+        .map(move |(username, site)| {
+            let checker = Arc::clone(&checker);
+            async move { checker.check_account(&site, &username).await }
+        })
+        .buffer_unordered(concurrency.max(1))
+}
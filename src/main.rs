@@ -1,54 +1,544 @@
-mod checker;
-mod sites;
-
-use checker::{AccountChecker, SiteResult};
-use sites::get_sites;
-use clap::Parser;
+use vidocq::checker::{AccountChecker, CheckResult, CheckerOptions, ProxyMode, SiteResult};
+use vidocq::permute::{permutations, PermuteOptions};
+use vidocq::pivot::{Discovery, LinkResolver};
+use vidocq::sites::{self, get_sites, load_sites, ManifestFormat, Site};
+use vidocq::fixture::{self, FixtureMode};
+use vidocq::history::{History, NewScan, ScanRecord};
+use vidocq::notify::{Notification, Notifier};
+use vidocq::diff::{self, ChangeKind};
+use vidocq::avatar::{self, AvatarCluster};
+use vidocq::selftest::{self, Health, SiteHealth};
+use vidocq::import::{self, SourceFormat};
+use vidocq::{export, report};
+use serde::Serialize;
+use clap::{Parser, Subcommand};
 use colored::*;
-use futures::stream::{self, StreamExt};
+use futures::StreamExt;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime};
 use indicatif::{ProgressBar, ProgressStyle};
 
 #[derive(Parser, Debug)]
 #[command(name = "vidocq")]
 #[command(about = "Advanced OSINT tool for username searching across 100+ platforms", long_about = None)]
+#[command(group(clap::ArgGroup::new("input").required(true).args(["username", "usernames_file"])))]
+#[command(subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Username to search for
     #[arg(short, long)]
-    username: String,
+    username: Option<String>,
+
+    /// Scan every username in a file, one per line (# starts a comment); use - for stdin
+    #[arg(short = 'U', long, value_name = "FILE")]
+    usernames_file: Option<PathBuf>,
+
+    /// Also check variants of each username: separators, leetspeak, numeric suffixes and name combinations
+    #[arg(long)]
+    permute: bool,
+
+    /// First name to combine with --last when generating variants
+    #[arg(long, requires = "permute")]
+    first: Option<String>,
+
+    /// Last name to combine with --first when generating variants
+    #[arg(long, requires = "permute")]
+    last: Option<String>,
+
+    /// Maximum variants per username with --permute, the username itself included
+    #[arg(long, default_value_t = 50, value_name = "N")]
+    max_variants: usize,
+
+    /// Follow links from found profiles to other catalogue sites and scan the linked usernames,
+    /// up to this many hops away (implies --extract)
+    #[arg(long, default_value_t = 0, value_name = "DEPTH")]
+    recurse: u32,
+
+    /// Record the scan in this SQLite database; `vidocq history` reads it back
+    #[arg(long, value_name = "FILE", global = true)]
+    db: Option<PathBuf>,
+
+    /// Also write a self-contained HTML report to this file
+    #[arg(long, value_name = "FILE", global = true)]
+    report: Option<PathBuf>,
 
     /// Maximum number of concurrent requests
-    #[arg(short, long, default_value_t = 20)]
+    #[arg(short, long, default_value_t = 20, global = true)]
     concurrency: usize,
 
     /// Show only found accounts
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     found_only: bool,
 
-    /// Output results as JSON
-    #[arg(short, long)]
+    /// Output results as JSON (same as --format json)
+    #[arg(short, long, conflicts_with = "format", global = true)]
     json: bool,
 
+    /// Stream results as newline-delimited JSON, one line per site as it finishes (same as --format ndjson)
+    #[arg(long, conflicts_with_all = ["format", "json"], global = true)]
+    ndjson: bool,
+
+    /// Output format: human, json, ndjson, csv or tsv
+    #[arg(long, default_value = "human", global = true)]
+    format: OutputFormat,
+
     /// Verbose output
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     verbose: bool,
+
+    /// Extract profile metadata (name, bio, avatar, location, links, join date) from found accounts
+    #[arg(long, global = true)]
+    extract: bool,
+
+    /// Download the avatars of found accounts and group accounts whose pictures match (implies --extract)
+    #[arg(long, global = true)]
+    avatars: bool,
+
+    /// Maximum differing bits (0-64) between two avatar hashes for them to count as the same picture
+    #[arg(long, default_value_t = avatar::DEFAULT_THRESHOLD, value_name = "BITS", value_parser = clap::value_parser!(u32).range(0..=64), global = true)]
+    avatar_threshold: u32,
+
+    /// Site catalogue to use instead of the built-in one (TOML or JSON)
+    #[arg(long, value_name = "FILE", global = true)]
+    sites: Option<PathBuf>,
+
+    /// Hide results below this confidence (0.0-1.0) in the human-readable output
+    #[arg(long, default_value_t = 0.0, value_parser = parse_confidence, global = true)]
+    min_confidence: f32,
+
+    /// Retries for transient failures (timeouts, 429, 5xx, network errors)
+    #[arg(long, default_value_t = 2, global = true)]
+    retries: u32,
+
+    /// Base delay before the first retry in milliseconds, doubled on each further attempt
+    #[arg(long, default_value_t = 500, value_name = "MS", global = true)]
+    retry_delay: u64,
+
+    /// Requests allowed in flight per host, for sites without their own rate_limit
+    #[arg(long, default_value_t = 2, global = true)]
+    host_concurrency: usize,
+
+    /// Minimum milliseconds between requests to the same host, for sites without their own rate_limit
+    #[arg(long, default_value_t = 250, value_name = "MS", global = true)]
+    host_interval: u64,
+
+    /// Route requests through a proxy (http://, https://, socks5:// or socks5h://); repeat for a proxy list
+    #[arg(long, value_name = "URL", value_parser = parse_proxy, global = true)]
+    proxy: Vec<String>,
+
+    /// Read proxies from a file, one URL per line (# starts a comment)
+    #[arg(long, value_name = "FILE", global = true)]
+    proxy_file: Option<PathBuf>,
+
+    /// How to spread requests over several proxies: round-robin or per-site
+    #[arg(long, default_value = "round-robin", global = true)]
+    proxy_mode: ProxyMode,
+
+    /// Save every response as a fixture in this directory, expecting the result it produced
+    #[arg(long, value_name = "DIR", global = true)]
+    record: Option<PathBuf>,
+
+    /// Answer every request from the fixtures in this directory instead of the network
+    #[arg(long, value_name = "DIR", conflicts_with = "record", global = true)]
+    replay: Option<PathBuf>,
+
+    /// Route everything through a local Tor SOCKS proxy (DNS included)
+    #[arg(long, conflicts_with_all = ["proxy", "proxy_file"], global = true)]
+    tor: bool,
+
+    /// SOCKS port of the local Tor daemon (9150 for Tor Browser)
+    #[arg(long, default_value_t = 9050, requires = "tor", global = true)]
+    tor_port: u16,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Re-scan on a schedule and send notifications when accounts appear, disappear or change
+    #[command(group(clap::ArgGroup::new("watch_input").required(true).args(["username", "usernames_file"])))]
+    Watch {
+        /// Username to watch
+        #[arg(short, long)]
+        username: Option<String>,
+
+        /// Watch every username in a file, one per line (# starts a comment); use - for stdin
+        #[arg(short = 'U', long, value_name = "FILE")]
+        usernames_file: Option<PathBuf>,
+
+        /// Time between scans, e.g. 90s, 30m, 6h or 1d (a plain number is seconds)
+        #[arg(long, default_value = "6h", value_parser = parse_interval)]
+        interval: Duration,
+
+        /// POST changes as JSON to this URL; repeat for several
+        #[arg(long, value_name = "URL")]
+        webhook: Vec<String>,
+
+        /// Run this shell command on changes, with the changes as JSON on stdin; repeat for several
+        #[arg(long, value_name = "COMMAND")]
+        exec: Vec<String>,
+
+        /// Append changes to this file, one JSON object per run; repeat for several
+        #[arg(long, value_name = "FILE")]
+        notify_file: Vec<PathBuf>,
+    },
+    /// Compare two scans and report accounts that appeared, disappeared or changed state.
+    /// Exits 0 when nothing changed, 1 when something did and 2 on errors
+    Diff {
+        /// Old scan: a --json/--ndjson result file, or a scan id from --db
+        old: String,
+        /// New scan: a --json/--ndjson result file, or a scan id from --db
+        new: String,
+    },
+    /// Browse scans recorded with --db
+    History {
+        #[command(subcommand)]
+        action: HistoryCommand,
+    },
+    /// Replay every fixture in a directory and check each result against the expected one
+    /// (-v lists passing fixtures too). Exits 0 when all pass, 1 when any fail and 2 on errors
+    Regress {
+        /// Fixture directory written by --record
+        dir: PathBuf,
+    },
+    /// Convert a Sherlock, WhatsMyName or Maigret site list into a vidocq catalogue, reporting the
+    /// entries that can't be mapped. Sites already in the catalogue (--sites or built-in) are left out
+    Import {
+        /// Sherlock data.json, WhatsMyName wmn-data.json or Maigret data.json
+        file: PathBuf,
+
+        /// Source project: sherlock, whatsmyname or maigret; detected from the file when omitted
+        #[arg(long, value_name = "FORMAT")]
+        from: Option<SourceFormat>,
+
+        /// Write the catalogue here instead of stdout (.json for JSON, TOML otherwise)
+        #[arg(long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Check every site's `claimed` and `unclaimed` sample usernames and report which sites no
+    /// longer detect accounts correctly (-v lists healthy sites too). Exits 0 when no site is
+    /// broken, 1 when any is and 2 on errors
+    Selftest {
+        /// Only test this site; repeat for several
+        #[arg(long = "site", value_name = "NAME")]
+        only: Vec<String>,
+    },
+    /// Run a local REST API for starting scans and streaming their results
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        bind: SocketAddr,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum HistoryCommand {
+    /// List recorded scans
+    List,
+    /// Print a recorded scan's results in any output format (--format, --json, --report, ...)
+    Show {
+        /// Scan id from `history list`; defaults to the latest scan
+        id: Option<i64>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    /// Colored text for the terminal
+    Human,
+    Json,
+    /// One JSON object per line, written as each check finishes
+    Ndjson,
+    Csv,
+    Tsv,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(OutputFormat::Human),
+            "json" => Ok(OutputFormat::Json),
+            "ndjson" => Ok(OutputFormat::Ndjson),
+            "csv" => Ok(OutputFormat::Csv),
+            "tsv" => Ok(OutputFormat::Tsv),
+            _ => Err(format!("unknown output format `{}` (expected human, json, ndjson, csv or tsv)", s)),
+        }
+    }
+}
+
+impl Args {
+    fn output_format(&self) -> OutputFormat {
+        if self.json {
+            OutputFormat::Json
+        } else if self.ndjson {
+            OutputFormat::Ndjson
+        } else {
+            self.format
+        }
+    }
+}
+
+fn parse_proxy(s: &str) -> Result<String, String> {
+    const SCHEMES: [&str; 4] = ["http://", "https://", "socks5://", "socks5h://"];
+    let s = s.trim();
+    if SCHEMES.iter().any(|scheme| s.to_lowercase().starts_with(scheme)) {
+        Ok(s.to_string())
+    } else {
+        Err(format!("`{}` is not a proxy URL (expected http://, https://, socks5:// or socks5h://)", s))
+    }
+}
+
+/// Usernames from --username or --usernames-file, deduplicated, in input order
+fn collect_usernames(username: Option<&String>, usernames_file: Option<&PathBuf>) -> Result<Vec<String>, String> {
+    let text = match (username, usernames_file) {
+        (Some(username), _) => username.clone(),
+        (None, Some(path)) if path.as_os_str() == "-" => {
+            std::io::read_to_string(std::io::stdin()).map_err(|e| format!("cannot read usernames from stdin: {}", e))?
+        }
+        (None, Some(path)) => std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read usernames file {}: {}", path.display(), e))?,
+        (None, None) => String::new(),
+    };
+
+    let mut usernames: Vec<String> = Vec::new();
+    for line in text.lines() {
+        let username = line.split('#').next().unwrap_or("").trim();
+        if !username.is_empty() && !usernames.iter().any(|u| u == username) {
+            usernames.push(username.to_string());
+        }
+    }
+    if usernames.is_empty() {
+        return Err("no usernames to search for".to_string());
+    }
+    Ok(usernames)
+}
+
+/// Usernames to check, each with the seed it was derived from when it is a --permute variant.
+/// A variant reachable from several seeds is checked once and attributed to the first.
+fn expand_usernames(args: &Args, usernames: Vec<String>) -> Vec<(String, Option<String>)> {
+    if !args.permute {
+        return usernames.into_iter().map(|username| (username, None)).collect();
+    }
+
+    let options = PermuteOptions {
+        first: args.first.clone(),
+        last: args.last.clone(),
+        max_variants: args.max_variants,
+    };
+    let mut targets: Vec<(String, Option<String>)> = Vec::new();
+    for seed in &usernames {
+        for variant in permutations(seed, &options) {
+            if targets.iter().any(|(username, _)| *username == variant) {
+                continue;
+            }
+            let seed = (variant != *seed).then(|| seed.clone());
+            targets.push((variant, seed));
+        }
+    }
+    targets
+}
+
+/// Proxies from --tor, --proxy and --proxy-file, in that order
+fn collect_proxies(args: &Args) -> Result<Vec<String>, String> {
+    if args.tor {
+        // socks5h so hostnames are resolved by Tor rather than leaking through local DNS
+        return Ok(vec![format!("socks5h://127.0.0.1:{}", args.tor_port)]);
+    }
+
+    let mut proxies = args.proxy.clone();
+    if let Some(path) = &args.proxy_file {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read proxy list {}: {}", path.display(), e))?;
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let proxy = parse_proxy(line).map_err(|e| format!("{} line {}: {}", path.display(), number + 1, e))?;
+            proxies.push(proxy);
+        }
+    }
+    Ok(proxies)
+}
+
+/// "90s", "30m", "6h", "1d" or a plain number of seconds
+fn parse_interval(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (number, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let value: u64 = number.parse().map_err(|_| format!("`{}` is not an interval (e.g. 90s, 30m, 6h, 1d)", s))?;
+    let unit_secs: u64 = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        _ => return Err(format!("unknown interval unit `{}` (expected s, m, h or d)", unit)),
+    };
+    let secs = value.checked_mul(unit_secs).ok_or_else(|| format!("interval `{}` is too long", s))?;
+    if secs == 0 {
+        return Err("interval must be longer than zero".to_string());
+    }
+    Ok(Duration::from_secs(secs))
+}
+
+/// The largest whole unit of `parse_interval` that fits
+fn format_interval(interval: Duration) -> String {
+    let secs = interval.as_secs();
+    match secs {
+        s if s % 86400 == 0 => format!("{}d", s / 86400),
+        s if s % 3600 == 0 => format!("{}h", s / 3600),
+        s if s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+fn parse_confidence(s: &str) -> Result<f32, String> {
+    let value: f32 = s.parse().map_err(|_| format!("`{}` is not a number", s))?;
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!("confidence must be between 0.0 and 1.0, got {}", value))
+    }
 }
 
 #[tokio::main]
 async fn main() {
     let args = Args::parse();
+
+    match &args.command {
+        Some(Command::History { action }) => run_history(&args, action),
+        Some(Command::Diff { old, new }) => run_diff(&args, old, new),
+        Some(Command::Regress { dir }) => run_regress(&args, dir).await,
+        Some(Command::Import { file, from, output }) => run_import(&args, file, *from, output.as_deref()),
+        Some(Command::Selftest { only }) => run_selftest(&args, only).await,
+        Some(Command::Watch { username, usernames_file, interval, webhook, exec, notify_file }) => {
+            let usernames = match collect_usernames(username.as_ref(), usernames_file.as_ref()) {
+                Ok(usernames) => usernames,
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".bright_red().bold(), e);
+                    std::process::exit(1);
+                }
+            };
+            let notifiers: Vec<Notifier> = webhook
+                .iter()
+                .cloned()
+                .map(Notifier::Webhook)
+                .chain(exec.iter().cloned().map(Notifier::Command))
+                .chain(notify_file.iter().cloned().map(Notifier::File))
+                .collect();
+            let sites = load_catalogue(&args);
+            let checker = build_checker(&args);
+            run_watch(&args, usernames, *interval, &notifiers, sites, checker).await
+        }
+        Some(Command::Serve { bind }) => {
+            let sites = load_catalogue(&args);
+            let checker = build_checker(&args);
+            eprintln!("{}", format!("Serving the vidocq API on http://{} ({} platforms)", bind, sites.len()).bright_cyan().bold());
+            if let Err(e) = vidocq::server::serve(*bind, checker, sites, args.concurrency).await {
+                eprintln!("{} cannot serve on {}: {}", "Error:".bright_red().bold(), bind, e);
+                std::process::exit(1);
+            }
+        }
+        None => {
+            let sites = load_catalogue(&args);
+            let checker = build_checker(&args);
+            run_scan(&args, sites, checker).await
+        }
+    }
+}
+
+/// The site catalogue from --sites, or the built-in one
+fn load_catalogue(args: &Args) -> Vec<Site> {
+    match &args.sites {
+        Some(path) => match load_sites(path) {
+            Ok(sites) => sites,
+            Err(e) => {
+                eprintln!("{} {}", "Error:".bright_red().bold(), e);
+                std::process::exit(1);
+            }
+        },
+        None => get_sites(),
+    }
+}
+
+/// --record or --replay, making sure a recording has somewhere to go
+fn fixture_mode(args: &Args) -> Option<FixtureMode> {
+    if let Some(dir) = &args.record {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            eprintln!("{} cannot create fixture directory {}: {}", "Error:".bright_red().bold(), dir.display(), e);
+            std::process::exit(1);
+        }
+        return Some(FixtureMode::Record(dir.clone()));
+    }
+    args.replay.clone().map(FixtureMode::Replay)
+}
+
+fn build_checker(args: &Args) -> Arc<AccountChecker> {
+    let proxies = match collect_proxies(args) {
+        Ok(proxies) => proxies,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".bright_red().bold(), e);
+            std::process::exit(1);
+        }
+    };
+    match AccountChecker::with_options(CheckerOptions {
+        retries: args.retries,
+        retry_delay: Duration::from_millis(args.retry_delay),
+        host_concurrency: args.host_concurrency.max(1),
+        host_interval: Duration::from_millis(args.host_interval),
+        proxies,
+        proxy_mode: args.proxy_mode,
+        extract_profiles: args.extract || args.recurse > 0 || args.avatars,
+        hash_avatars: args.avatars,
+        fixtures: fixture_mode(args),
+    }) {
+        Ok(checker) => Arc::new(checker),
+        Err(e) => {
+            eprintln!("{} invalid proxy: {}", "Error:".bright_red().bold(), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Scan the usernames from the command line and print the results
+async fn run_scan(args: &Args, sites: Vec<Site>, checker: Arc<AccountChecker>) {
     let start_time = Instant::now();
+    let started = SystemTime::now();
 
-    let sites = get_sites();
-    let checker = Arc::new(AccountChecker::new());
-    let username = args.username.clone();
+    let usernames = match collect_usernames(args.username.as_ref(), args.usernames_file.as_ref()) {
+        Ok(usernames) => usernames,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".bright_red().bold(), e);
+            std::process::exit(1);
+        }
+    };
+    let seeds = usernames.len();
+    let targets = expand_usernames(args, usernames);
+    let batch = args.usernames_file.is_some() || args.permute;
+    let format = args.output_format();
+    // Keep stdout clean for output that gets piped or redirected into other tools
+    let machine_readable = format != OutputFormat::Human;
 
-    println!("{}", format!("Searching for username: {}", username).bright_cyan().bold());
-    println!("{}", format!("Checking {} platforms...", sites.len()).bright_white());
+    let banner = if args.permute {
+        format!("Searching for {} usernames ({} seed{} and variants)", targets.len(), seeds, if seeds == 1 { "" } else { "s" })
+    } else if batch {
+        format!("Searching for {} usernames", targets.len())
+    } else {
+        format!("Searching for username: {}", targets[0].0)
+    };
+    status(machine_readable, banner.bright_cyan().bold());
+    status(machine_readable, format!("Checking {} platforms...", sites.len()).bright_white());
 
     // Create progress bar wrapped in Arc<Mutex> for sharing across async tasks
-    let pb = Arc::new(Mutex::new(ProgressBar::new(sites.len() as u64)));
+    let pb = Arc::new(Mutex::new(ProgressBar::new((sites.len() * targets.len()) as u64)));
     pb.lock().unwrap().set_style(
         ProgressStyle::default_bar()
             .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos:}/{len} ({eta})")
@@ -56,66 +546,977 @@ async fn main() {
             .progress_chars("#>-"),
     );
 
-    // Process sites concurrently
-    let results: Vec<SiteResult> = stream::iter(sites.iter())
-        .map(|site| {
-            let checker = Arc::clone(&checker);
-            let username = username.clone();
-            let site = site.clone();
-            let pb = Arc::clone(&pb);
-            async move {
-                let result = checker.check_account(&site, &username).await;
+    // Process every username x site pair through the same concurrency limit
+    let seed_of: HashMap<&str, &Option<String>> = targets.iter().map(|(username, seed)| (username.as_str(), seed)).collect();
+    let mut usernames: Vec<String> = targets.iter().map(|(username, _)| username.clone()).collect();
+    let resolver = LinkResolver::new(&sites);
+    let mut seen: HashSet<String> = usernames.iter().map(|username| username.to_lowercase()).collect();
+    let mut discoveries: Vec<Discovery> = Vec::new();
+    let mut results: Vec<SiteResult> = Vec::new();
+    let mut round = usernames.clone();
+    let mut depth = 0;
+    loop {
+        let round_results: Vec<SiteResult> = vidocq::scan_with(Arc::clone(&checker), round, sites.clone(), args.concurrency)
+            .map(|mut result| {
+                result.seed = seed_of.get(result.username.as_str()).and_then(|seed| (*seed).clone());
                 pb.lock().unwrap().inc(1);
                 result
+            })
+            .inspect(|result| {
+                if format == OutputFormat::Ndjson {
+                    // Hide the progress bar while writing so the two don't interleave on a terminal
+                    pb.lock().unwrap().suspend(|| output_ndjson_line(result));
+                }
+            })
+            .collect()
+            .await;
+
+        depth += 1;
+        let mut next = Vec::new();
+        if depth <= args.recurse {
+            for discovery in round_results.iter().flat_map(|result| resolver.discover(result, depth)) {
+                if seen.insert(discovery.username.to_lowercase()) {
+                    next.push(discovery.username.clone());
+                }
+                discoveries.push(discovery);
+            }
+        }
+        results.extend(round_results);
+        if next.is_empty() {
+            break;
+        }
+
+        pb.lock().unwrap().suspend(|| {
+            status(machine_readable, format!("Following {} linked username{}: {}", next.len(), if next.len() == 1 { "" } else { "s" }, next.join(", ")).bright_cyan());
+        });
+        pb.lock().unwrap().inc_length((sites.len() * next.len()) as u64);
+        usernames.extend(next.iter().cloned());
+        round = next;
+    }
+
+    pb.lock().unwrap().finish_with_message("Complete!");
+
+    let duration = start_time.elapsed();
+    // Recursive scans always use the per-username shape, whether or not any link was followed,
+    // so scripts get the same JSON structure for every run with the same flags
+    let batch = batch || args.recurse > 0;
+    if format != OutputFormat::Ndjson {
+        render_results(args, &usernames, &results, &discoveries, batch);
+    }
+    if let Some(path) = &args.report {
+        let meta = report::ReportMeta {
+            usernames: &usernames,
+            started,
+            duration,
+            site_count: sites.len(),
+            discoveries: &discoveries,
+            avatar_clusters: &avatar_clusters(args, &usernames, &results),
+        };
+        write_report(args, path, &meta, &results);
+    }
+    if let Some(path) = &args.db {
+        let options = recorded_options(args);
+        let rows = ordered(&group_results(&usernames, &results));
+        let recorded = History::open(path).and_then(|mut history| {
+            history.record_scan(&NewScan {
+                usernames: &usernames,
+                started,
+                duration,
+                site_count: sites.len(),
+                options: &options,
+                results: &rows,
+                discoveries: &discoveries,
+            })
+        });
+        match recorded {
+            Ok(id) => status(machine_readable, format!("\nRecorded as scan #{} in {}", id, path.display()).bright_white()),
+            Err(e) => {
+                eprintln!("{} {}", "Error:".bright_red().bold(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+    status(machine_readable, format!("\nCompleted in {:.2} seconds", duration.as_secs_f64()).bright_white());
+}
+
+/// Group results per username, keeping input order
+fn group_results<'a>(usernames: &'a [String], results: &'a [SiteResult]) -> Vec<(&'a String, Vec<&'a SiteResult>)> {
+    usernames
+        .iter()
+        .map(|username| (username, results.iter().filter(|r| &r.username == username).collect()))
+        .collect()
+}
+
+/// Print results in the chosen output format
+fn render_results(args: &Args, usernames: &[String], results: &[SiteResult], discoveries: &[Discovery], batch: bool) {
+    let groups = group_results(usernames, results);
+    let clusters = avatar_clusters(args, usernames, results);
+    let clusters = args.avatars.then_some(clusters.as_slice());
+    let format = args.output_format();
+    match format {
+        OutputFormat::Json if batch => output_json_grouped(&groups, discoveries, clusters),
+        OutputFormat::Json => output_json(results, clusters),
+        OutputFormat::Ndjson => {
+            for result in ordered(&groups) {
+                output_ndjson_line(result);
+            }
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            let delimiter = if format == OutputFormat::Csv { ',' } else { '\t' };
+            output_delimited(&ordered(&groups), delimiter);
+        }
+        OutputFormat::Human => {
+            for (username, group) in &groups {
+                let linked_from: Vec<String> = discoveries
+                    .iter()
+                    .filter(|d| d.username.eq_ignore_ascii_case(username) && !d.from_username.eq_ignore_ascii_case(username))
+                    .map(|d| format!("{} on {}", d.from_username, d.from_site))
+                    .collect();
+                if let Some(seed) = group.first().and_then(|r| r.seed.as_ref()) {
+                    println!("\n{} {}", format!("━━━ {} ━━━", username).bright_cyan().bold(), format!("(variant of {})", seed).bright_black());
+                } else if !linked_from.is_empty() {
+                    println!("\n{} {}", format!("━━━ {} ━━━", username).bright_cyan().bold(), format!("(linked from {})", linked_from.join(", ")).bright_black());
+                } else if batch {
+                    println!("\n{}", format!("━━━ {} ━━━", username).bright_cyan().bold());
+                }
+                output_human_readable(args, group);
+            }
+            if batch {
+                output_batch_summary(&groups);
+            }
+            if !discoveries.is_empty() {
+                output_discoveries(discoveries);
+            }
+            if let Some(clusters) = clusters {
+                output_avatar_clusters(clusters);
+            }
+        }
+    }
+}
+
+/// "Likely same owner" groups from the avatar hashes, with --avatars
+fn avatar_clusters(args: &Args, usernames: &[String], results: &[SiteResult]) -> Vec<AvatarCluster> {
+    if !args.avatars {
+        return Vec::new();
+    }
+    avatar::clusters(&ordered(&group_results(usernames, results)), args.avatar_threshold)
+}
+
+fn write_report(args: &Args, path: &Path, meta: &report::ReportMeta, results: &[SiteResult]) {
+    let rows = ordered(&group_results(meta.usernames, results));
+    match std::fs::write(path, report::render_html(meta, &rows)) {
+        Ok(()) => status(args.output_format() != OutputFormat::Human, format!("\nReport written to {}", path.display()).bright_white()),
+        Err(e) => {
+            eprintln!("{} cannot write report {}: {}", "Error:".bright_red().bold(), path.display(), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// The options that shaped a scan, as stored with it in the history database.
+/// Proxy URLs are left out since they may carry credentials.
+#[derive(Serialize)]
+struct RecordedOptions<'a> {
+    sites: Option<&'a PathBuf>,
+    concurrency: usize,
+    retries: u32,
+    retry_delay_ms: u64,
+    host_concurrency: usize,
+    host_interval_ms: u64,
+    proxies: usize,
+    tor: bool,
+    permute: bool,
+    first: Option<&'a str>,
+    last: Option<&'a str>,
+    extract: bool,
+    recurse: u32,
+    avatars: bool,
+    avatar_threshold: u32,
+    replay: Option<&'a PathBuf>,
+}
+
+fn recorded_options(args: &Args) -> serde_json::Value {
+    let options = RecordedOptions {
+        sites: args.sites.as_ref(),
+        concurrency: args.concurrency,
+        retries: args.retries,
+        retry_delay_ms: args.retry_delay,
+        host_concurrency: args.host_concurrency,
+        host_interval_ms: args.host_interval,
+        proxies: args.proxy.len() + usize::from(args.proxy_file.is_some()),
+        tor: args.tor,
+        permute: args.permute,
+        first: args.first.as_deref(),
+        last: args.last.as_deref(),
+        extract: args.extract,
+        recurse: args.recurse,
+        avatars: args.avatars,
+        avatar_threshold: args.avatar_threshold,
+        replay: args.replay.as_ref(),
+    };
+    serde_json::to_value(options).unwrap_or_default()
+}
+
+fn run_history(args: &Args, action: &HistoryCommand) {
+    let Some(path) = &args.db else {
+        eprintln!("{} `vidocq history` needs --db <FILE>", "Error:".bright_red().bold());
+        std::process::exit(1);
+    };
+    let history = match History::open(path) {
+        Ok(history) => history,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".bright_red().bold(), e);
+            std::process::exit(1);
+        }
+    };
+
+    let outcome = match action {
+        HistoryCommand::List => history.list_scans().map(|scans| output_scan_list(args, &scans)),
+        HistoryCommand::Show { id } => {
+            let id = match id {
+                Some(id) => Ok(Some(*id)),
+                None => history.latest_scan_id(),
+            };
+            match id {
+                Ok(Some(id)) => history.load_scan(id).and_then(|(record, results)| {
+                    let discoveries = history.load_discoveries(id)?;
+                    let batch = record.usernames.len() > 1;
+                    render_results(args, &record.usernames, &results, &discoveries, batch);
+                    if let Some(path) = &args.report {
+                        let meta = report::ReportMeta {
+                            usernames: &record.usernames,
+                            started: record.started,
+                            duration: record.duration,
+                            site_count: record.site_count,
+                            discoveries: &discoveries,
+                            avatar_clusters: &avatar_clusters(args, &record.usernames, &results),
+                        };
+                        write_report(args, path, &meta, &results);
+                    }
+                    Ok(())
+                }),
+                Ok(None) => {
+                    eprintln!("{} no scans recorded in {}", "Error:".bright_red().bold(), path.display());
+                    std::process::exit(1);
+                }
+                Err(e) => Err(e),
+            }
+        }
+    };
+    if let Err(e) = outcome {
+        eprintln!("{} {}", "Error:".bright_red().bold(), e);
+        std::process::exit(1);
+    }
+}
+
+/// Scan `usernames` every `interval` until interrupted, notifying whenever a run differs from the one before.
+/// Only verdicts are compared: a site that times out or rate-limits keeps its last found/not found result.
+/// With --db, every run is recorded and the first run is compared with the latest recorded scan of the same usernames.
+async fn run_watch(args: &Args, usernames: Vec<String>, interval: Duration, notifiers: &[Notifier], sites: Vec<Site>, checker: Arc<AccountChecker>) {
+    let fail = |e: &dyn std::fmt::Display| -> ! {
+        eprintln!("{} {}", "Error:".bright_red().bold(), e);
+        std::process::exit(1);
+    };
+    let mut history = args.db.as_ref().map(|path| History::open(path).unwrap_or_else(|e| fail(&e)));
+
+    // Pick up where an earlier watch of the same usernames left off
+    let mut previous: Option<Vec<SiteResult>> = None;
+    if let Some(history) = &history {
+        let scans = history.list_scans().unwrap_or_else(|e| fail(&e));
+        if let Some(scan) = scans.iter().rev().find(|scan| scan.usernames == usernames) {
+            let (_, results) = history.load_scan(scan.id).unwrap_or_else(|e| fail(&e));
+            println!("{}", format!("Comparing against scan #{} from {}", scan.id, httpdate::fmt_http_date(scan.started)).bright_white());
+            previous = Some(results);
+        }
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap_or_else(|e| fail(&e));
+
+    println!(
+        "{}",
+        format!("Watching {} username{} on {} platforms every {}", usernames.len(), if usernames.len() == 1 { "" } else { "s" }, sites.len(), format_interval(interval))
+            .bright_cyan()
+            .bold()
+    );
+
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    for run in 1.. {
+        ticker.tick().await;
+        let start_time = Instant::now();
+        let started = SystemTime::now();
+        let results: Vec<SiteResult> = vidocq::scan_with(Arc::clone(&checker), usernames.clone(), sites.clone(), args.concurrency)
+            .collect()
+            .await;
+        let found = results.iter().filter(|r| r.result == CheckResult::Found).count();
+
+        if let Some(history) = &mut history {
+            let rows = ordered(&group_results(&usernames, &results));
+            let scan = NewScan {
+                usernames: &usernames,
+                started,
+                duration: start_time.elapsed(),
+                site_count: sites.len(),
+                options: &recorded_options(args),
+                results: &rows,
+                discoveries: &[],
+            };
+            if let Err(e) = history.record_scan(&scan) {
+                eprintln!("{} {}", "Warning:".bright_yellow().bold(), e);
+            }
+        }
+
+        let stamp = format!("[{}] run #{}:", httpdate::fmt_http_date(started), run).bright_black();
+        let Some(before) = &previous else {
+            println!("{} {} found (baseline)", stamp, found);
+            previous = Some(results);
+            continue;
+        };
+
+        let (changes, settled) = diff::diff_conclusive(before, &results);
+        if changes.is_empty() {
+            println!("{} {} found, no changes", stamp, found);
+        } else {
+            println!("{} {} found, {}", stamp, found, format!("{} change{}", changes.len(), if changes.len() == 1 { "" } else { "s" }).bright_yellow().bold());
+            output_changes(&changes);
+            let notification = Notification { usernames: &usernames, checked_at: httpdate::fmt_http_date(started), changes: &changes };
+            for notifier in notifiers {
+                if let Err(e) = notifier.send(&client, &notification).await {
+                    eprintln!("{} notification failed: {}", "Warning:".bright_yellow().bold(), e);
+                }
+            }
+        }
+        previous = Some(settled);
+    }
+}
+
+fn run_diff(args: &Args, old: &str, new: &str) {
+    let load = |source: &str| -> Result<Vec<SiteResult>, String> {
+        // A bare number that isn't a file is a scan id from the history database
+        let path = Path::new(source);
+        match source.parse::<i64>() {
+            Ok(id) if !path.exists() => {
+                let db = args.db.as_ref().ok_or_else(|| format!("`{}` is not a file; pass --db to read scan ids from history", source))?;
+                let history = History::open(db).map_err(|e| e.to_string())?;
+                history.load_scan(id).map(|(_, results)| results).map_err(|e| e.to_string())
+            }
+            _ => diff::load_results(path),
+        }
+    };
+    let (old_results, new_results) = match load(old).and_then(|o| load(new).map(|n| (o, n))) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".bright_red().bold(), e);
+            std::process::exit(2);
+        }
+    };
+
+    let changes = diff::diff(&old_results, &new_results);
+    match args.output_format() {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&changes).unwrap()),
+        OutputFormat::Ndjson => {
+            for change in &changes {
+                println!("{}", serde_json::to_string(change).unwrap());
+            }
+        }
+        OutputFormat::Csv => output_changes_delimited(&changes, ','),
+        OutputFormat::Tsv => output_changes_delimited(&changes, '\t'),
+        OutputFormat::Human => output_changes(&changes),
+    }
+    std::process::exit(if changes.is_empty() { 0 } else { 1 });
+}
+
+/// Outcome of replaying one fixture
+#[derive(Serialize)]
+struct RegressionResult {
+    path: String,
+    site: String,
+    username: String,
+    expect: String,
+    actual: String,
+    passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+async fn run_regress(args: &Args, dir: &Path) {
+    let fail = |e: &dyn std::fmt::Display| -> ! {
+        eprintln!("{} {}", "Error:".bright_red().bold(), e);
+        std::process::exit(2);
+    };
+    let fixtures = fixture::load_all(dir).unwrap_or_else(|e| fail(&e));
+    if fixtures.is_empty() {
+        fail(&format!("no fixtures in {}", dir.display()));
+    }
+    let sites = load_catalogue(args);
+    // No retries: a replayed response never changes, so retrying a recorded 429 would only wait
+    let options = CheckerOptions { retries: 0, fixtures: Some(FixtureMode::Replay(dir.to_path_buf())), ..CheckerOptions::default() };
+    let checker = AccountChecker::with_options(options).unwrap_or_else(|e| fail(&e));
+
+    let results: Vec<RegressionResult> = futures::stream::iter(fixtures)
+        .map(|(path, fixture)| {
+            let site = sites.iter().find(|site| site.name == fixture.site);
+            let checker = &checker;
+            async move {
+                let (actual, detail) = match site {
+                    Some(site) => {
+                        let result = checker.check_account(site, &fixture.username).await;
+                        let detail = result.evidence.iter().map(|e| e.detail.as_str()).collect::<Vec<_>>().join("; ");
+                        (result.result.tag().to_string(), Some(detail).filter(|d| !d.is_empty()))
+                    }
+                    None => ("missing".to_string(), Some(format!("{} is not in the site catalogue", fixture.site))),
+                };
+                RegressionResult {
+                    path: path.display().to_string(),
+                    passed: actual == fixture.expect,
+                    site: fixture.site,
+                    username: fixture.username,
+                    expect: fixture.expect,
+                    actual,
+                    detail,
+                }
             }
         })
-        .buffer_unordered(args.concurrency)
+        .buffered(args.concurrency.max(1))
         .collect()
         .await;
 
-    pb.lock().unwrap().finish_with_message("Complete!");
+    let failed = results.iter().filter(|r| !r.passed).count();
+    if args.output_format() == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&results).unwrap());
+    } else {
+        for result in &results {
+            if result.passed {
+                if args.verbose {
+                    println!("  {} {} / {} {}", "✓".bright_green(), result.site.bright_white(), result.username, result.actual.bright_black());
+                }
+            } else {
+                println!(
+                    "  {} {} / {} {}",
+                    "✗".bright_red(),
+                    result.site.bright_white(),
+                    result.username,
+                    format!("expected {}, got {}", result.expect, result.actual).bright_red()
+                );
+                if let Some(detail) = &result.detail {
+                    println!("      {} {}", "·".bright_black(), detail.bright_black());
+                }
+                println!("      {} {}", "·".bright_black(), result.path.bright_black());
+            }
+        }
+        let summary = format!("\n{} passed, {} failed", results.len() - failed, failed);
+        println!("{}", if failed == 0 { summary.bright_green().bold() } else { summary.bright_red().bold() });
+    }
+    std::process::exit(if failed == 0 { 0 } else { 1 });
+}
 
-    // Filter and sort results
-    let mut found_results: Vec<&SiteResult> = results
-        .iter()
-        .filter(|r| matches!(r.result, checker::CheckResult::Found))
-        .collect();
+fn run_import(args: &Args, file: &Path, from: Option<SourceFormat>, output: Option<&Path>) {
+    let fail = |e: &dyn std::fmt::Display| -> ! {
+        eprintln!("{} {}", "Error:".bright_red().bold(), e);
+        std::process::exit(1);
+    };
+    let text = std::fs::read_to_string(file).unwrap_or_else(|e| fail(&format!("cannot read {}: {}", file.display(), e)));
+    let data: serde_json::Value = serde_json::from_str(&text).unwrap_or_else(|e| fail(&format!("{} is not valid JSON: {}", file.display(), e)));
+    let format = from
+        .or_else(|| SourceFormat::detect(&data))
+        .unwrap_or_else(|| fail(&format!("cannot tell which project {} comes from; pass --from", file.display())));
+    let mut imported = import::import(&data, format).unwrap_or_else(|e| fail(&e));
+
+    let catalogue = load_catalogue(args);
+    let total = imported.sites.len() + imported.skipped.len();
+    let before = imported.sites.len();
+    imported.sites.retain(|site| !catalogue.iter().any(|known| known.name.eq_ignore_ascii_case(&site.name)));
+    let existing = before - imported.sites.len();
+
+    eprintln!("{}", format!("Imported {} of {} {} entries", imported.sites.len(), total, format.name()).bright_cyan().bold());
+    if existing > 0 {
+        eprintln!("{}", format!("Left out {} already in the catalogue", existing).bright_black());
+    }
+    if !imported.skipped.is_empty() {
+        eprintln!("\n{}", format!("Could not map {} entries:", imported.skipped.len()).bright_yellow().bold());
+        for note in &imported.skipped {
+            eprintln!("  {} {}", note.name.bright_white(), note.message.bright_black());
+        }
+    }
+    if !imported.warnings.is_empty() {
+        if args.verbose {
+            eprintln!("\n{}", format!("Imported with changes ({}):", imported.warnings.len()).bright_yellow().bold());
+            for note in &imported.warnings {
+                eprintln!("  {} {}", note.name.bright_white(), note.message.bright_black());
+            }
+        } else {
+            eprintln!("{}", format!("{} entries imported with changes (-v to list them)", imported.warnings.len()).bright_black());
+        }
+    }
+    if imported.sites.is_empty() {
+        fail(&"nothing to write");
+    }
 
-    let mut not_found_results: Vec<&SiteResult> = results
+    let manifest_format = output.map_or(ManifestFormat::Toml, ManifestFormat::from_path);
+    let manifest = sites::to_manifest(&imported.sites, manifest_format).unwrap_or_else(|e| fail(&e));
+    match output {
+        Some(path) => {
+            std::fs::write(path, manifest).unwrap_or_else(|e| fail(&format!("cannot write {}: {}", path.display(), e)));
+            eprintln!("{}", format!("Wrote {} sites to {}", imported.sites.len(), path.display()).bright_green().bold());
+        }
+        None => print!("{}", manifest),
+    }
+}
+
+async fn run_selftest(args: &Args, only: &[String]) {
+    let mut sites = load_catalogue(args);
+    if !only.is_empty() {
+        if let Some(unknown) = only.iter().find(|name| !sites.iter().any(|site| site.name.eq_ignore_ascii_case(name))) {
+            eprintln!("{} {} is not in the site catalogue", "Error:".bright_red().bold(), unknown);
+            std::process::exit(2);
+        }
+        sites.retain(|site| only.iter().any(|name| site.name.eq_ignore_ascii_case(name)));
+    }
+    // Unsupported sites are never requested, so there is nothing to test
+    sites.retain(|site| site.unsupported.is_none());
+    let checker = build_checker(args);
+    let jobs: Vec<(usize, String, CheckResult)> = sites
         .iter()
-        .filter(|r| matches!(r.result, checker::CheckResult::NotFound))
+        .enumerate()
+        .flat_map(|(index, site)| selftest::samples(site).into_iter().map(move |(username, expect)| (index, username, expect)))
         .collect();
 
-    let error_results: Vec<&SiteResult> = results
+    let machine_readable = args.output_format() != OutputFormat::Human;
+    let with_claimed = sites.iter().filter(|site| site.claimed.is_some()).count();
+    status(machine_readable, format!("Testing {} platforms ({} with a claimed sample)...", sites.len(), with_claimed).bright_cyan().bold());
+    let pb = ProgressBar::new(jobs.len() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos:}/{len} ({eta})")
+            .unwrap()
+            .progress_chars("#>-"),
+    );
+
+    let mut results: Vec<Vec<(CheckResult, SiteResult)>> = vec![Vec::new(); sites.len()];
+    let finished: Vec<(usize, CheckResult, SiteResult)> = futures::stream::iter(jobs)
+        .map(|(index, username, expect)| {
+            let (checker, site, pb) = (&checker, &sites[index], &pb);
+            async move {
+                let result = checker.check_account(site, &username).await;
+                pb.inc(1);
+                (index, expect, result)
+            }
+        })
+        .buffer_unordered(args.concurrency.max(1))
+        .collect()
+        .await;
+    pb.finish_and_clear();
+    for (index, expect, result) in finished {
+        results[index].push((expect, result));
+    }
+    let mut report: Vec<SiteHealth> = sites
         .iter()
-        .filter(|r| matches!(r.result, checker::CheckResult::Error(_)))
+        .zip(results)
+        .map(|(site, mut results)| {
+            // Claimed sample first, whichever finished first
+            results.sort_by_key(|(expect, _)| *expect != CheckResult::Found);
+            selftest::assess(site, results)
+        })
         .collect();
+    report.sort_by(|a, b| a.health.cmp(&b.health).then(a.category.cmp(&b.category)).then(a.site.cmp(&b.site)));
 
-    found_results.sort_by(|a, b| a.category.cmp(&b.category).then(a.site.cmp(&b.site)));
-    not_found_results.sort_by(|a, b| a.category.cmp(&b.category).then(a.site.cmp(&b.site)));
+    let broken = report.iter().filter(|site| site.health == Health::Broken).count();
+    if args.output_format() == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else {
+        output_health(args, &report);
+    }
+    std::process::exit(if broken == 0 { 0 } else { 1 });
+}
+
+/// The selftest report: broken and inconclusive sites with each failing sample, healthy ones with -v
+fn output_health(args: &Args, report: &[SiteHealth]) {
+    let sections = [
+        (Health::Broken, "✗ BROKEN"),
+        (Health::Inconclusive, "? INCONCLUSIVE"),
+        (Health::Healthy, "✓ HEALTHY"),
+    ];
+    for (health, title) in sections {
+        let matching: Vec<&SiteHealth> = report.iter().filter(|site| site.health == health).collect();
+        if matching.is_empty() || (health == Health::Healthy && !args.verbose) {
+            continue;
+        }
+        let heading = format!("{} ({})", title, matching.len());
+        let heading = match health {
+            Health::Broken => heading.bright_red().bold(),
+            Health::Inconclusive => heading.bright_yellow().bold(),
+            Health::Healthy => heading.bright_green().bold(),
+        };
+        println!("\n{}", heading);
+        for site in matching {
+            let note = if site.has_claimed { String::new() } else { " (no claimed sample)".to_string() };
+            println!("  {} {}{}", site.site.bright_white(), format!("[{}]", site.category).bright_black(), note.bright_black());
+            for check in site.checks.iter().filter(|check| !check.passed || args.verbose) {
+                let marker = if check.passed { "✓".bright_green() } else { "✗".bright_red() };
+                let outcome = format!("expected {}, got {}", check.expect, check.actual);
+                let outcome = if check.passed { outcome.bright_black() } else { outcome.normal() };
+                println!("      {} {} {}", marker, check.username, outcome);
+                if let Some(detail) = &check.detail {
+                    println!("          {} {}", "·".bright_black(), detail.bright_black());
+                }
+            }
+        }
+    }
+
+    let count = |health: Health| report.iter().filter(|site| site.health == health).count();
+    let untested = report.iter().filter(|site| !site.has_claimed).count();
+    let summary = format!(
+        "\n{} healthy, {} inconclusive, {} broken ({} without a claimed sample)",
+        count(Health::Healthy),
+        count(Health::Inconclusive),
+        count(Health::Broken),
+        untested
+    );
+    println!("{}", if count(Health::Broken) == 0 { summary.bright_green().bold() } else { summary.bright_red().bold() });
+}
+
+fn output_changes(changes: &[diff::Change]) {
+    if changes.is_empty() {
+        println!("{}", "No changes".bright_white());
+        return;
+    }
+
+    let sections = [
+        (ChangeKind::Appeared, "+ APPEARED", "+"),
+        (ChangeKind::Disappeared, "- DISAPPEARED", "-"),
+        (ChangeKind::Changed, "~ CHANGED", "~"),
+    ];
+    for (kind, title, marker) in sections {
+        let matching: Vec<&diff::Change> = changes.iter().filter(|c| c.change == kind).collect();
+        if matching.is_empty() {
+            continue;
+        }
+        let heading = format!("{} ({})", title, matching.len());
+        let heading = match kind {
+            ChangeKind::Appeared => heading.bright_green().bold(),
+            ChangeKind::Disappeared => heading.bright_yellow().bold(),
+            ChangeKind::Changed => heading.bright_red().bold(),
+        };
+        println!("\n{}", heading);
+        for change in matching {
+            let transition = format!(
+                "{} → {}",
+                change.old.as_deref().unwrap_or("not checked"),
+                change.new.as_deref().unwrap_or("not checked")
+            );
+            println!(
+                "  {} {} {} - {} {}",
+                marker,
+                change.username.bright_cyan(),
+                change.site.bright_white(),
+                change.url.bright_blue().underline(),
+                format!("({})", transition).bright_black()
+            );
+        }
+    }
+}
+
+/// One line of `history list --json`
+#[derive(Serialize)]
+struct ScanListing<'a> {
+    id: i64,
+    started: String,
+    duration_secs: f64,
+    usernames: &'a [String],
+    site_count: usize,
+    found: usize,
+    total: usize,
+    options: &'a serde_json::Value,
+}
+
+/// Past scans as a table, or as JSON with --json/--format json
+fn output_scan_list(args: &Args, scans: &[ScanRecord]) {
+    if args.output_format() == OutputFormat::Json {
+        let json: Vec<ScanListing> = scans
+            .iter()
+            .map(|scan| ScanListing {
+                id: scan.id,
+                started: httpdate::fmt_http_date(scan.started),
+                duration_secs: scan.duration.as_secs_f64(),
+                usernames: &scan.usernames,
+                site_count: scan.site_count,
+                found: scan.found,
+                total: scan.total,
+                options: &scan.options,
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&json).unwrap());
+        return;
+    }
 
-    // Output results
-    if args.json {
-        output_json(&results);
+    if scans.is_empty() {
+        println!("{}", "No scans recorded yet".bright_white());
+        return;
+    }
+    println!("  {:>5}  {:<29}  {:>6}  {:>6}  Usernames", "ID", "Started", "Found", "Total");
+    for scan in scans {
+        let mut usernames = scan.usernames.iter().take(3).cloned().collect::<Vec<_>>().join(", ");
+        if scan.usernames.len() > 3 {
+            usernames.push_str(&format!(" (+{} more)", scan.usernames.len() - 3));
+        }
+        println!(
+            "  {:>5}  {:<29}  {:>6}  {:>6}  {}",
+            scan.id,
+            httpdate::fmt_http_date(scan.started),
+            scan.found.to_string().bright_green().bold(),
+            scan.total,
+            usernames
+        );
+    }
+}
+
+/// Print a progress/status line, on stderr when stdout carries machine-readable output
+fn status(to_stderr: bool, line: ColoredString) {
+    if to_stderr {
+        eprintln!("{}", line);
     } else {
-        output_human_readable(&args, &found_results, &not_found_results, &error_results);
+        println!("{}", line);
     }
+}
 
-    let duration = start_time.elapsed();
-    println!("\n{}", format!("Completed in {:.2} seconds", duration.as_secs_f64()).bright_white());
+/// Inconclusive outcomes broken out in the summary, as (tag, label)
+const ERROR_KINDS: [(&str, &str); 10] = [
+    ("rate_limited", "Rate Limited"),
+    ("challenge", "Challenge"),
+    ("blocked", "Blocked"),
+    ("dns_error", "DNS Error"),
+    ("tls_error", "TLS Error"),
+    ("invalid_username", "Invalid Username"),
+    ("unsupported", "Unsupported"),
+    ("timeout", "Timeout"),
+    ("unavailable", "Unavailable"),
+    ("error", "Other Error"),
+];
+
+fn output_json(results: &[SiteResult], clusters: Option<&[AvatarCluster]>) {
+    print_json(&results, clusters);
+}
+
+/// With --avatars, JSON output becomes `{"results": [...], "likely_same_owner": [...]}`
+fn print_json<T: Serialize>(results: &T, clusters: Option<&[AvatarCluster]>) {
+    #[derive(Serialize)]
+    struct WithClusters<'a, T> {
+        results: &'a T,
+        likely_same_owner: &'a [AvatarCluster],
+    }
+    let json = match clusters {
+        Some(likely_same_owner) => serde_json::to_string_pretty(&WithClusters { results, likely_same_owner }),
+        None => serde_json::to_string_pretty(results),
+    };
+    println!("{}", json.unwrap());
+}
+
+/// All results flattened, usernames in input order and sites sorted by category within each
+fn ordered<'a>(groups: &[(&String, Vec<&'a SiteResult>)]) -> Vec<&'a SiteResult> {
+    let mut rows = Vec::new();
+    for (_, results) in groups {
+        let mut results = results.clone();
+        results.sort_by(|a, b| a.category.cmp(&b.category).then(a.site.cmp(&b.site)));
+        rows.extend(results);
+    }
+    rows
 }
 
-fn output_json(results: &[SiteResult]) {
-    let json = serde_json::to_string_pretty(results).unwrap();
-    println!("{}", json);
+/// CSV or TSV with one row per result
+fn output_delimited(rows: &[&SiteResult], delimiter: char) {
+    if let Err(e) = export::write_delimited(std::io::stdout().lock(), rows, delimiter) {
+        eprintln!("{} cannot write results: {}", "Error:".bright_red().bold(), e);
+        std::process::exit(1);
+    }
 }
 
-fn output_human_readable(
-    args: &Args,
-    found: &[&SiteResult],
-    not_found: &[&SiteResult],
-    errors: &[&SiteResult],
-) {
+/// CSV or TSV with one row per change between two scans
+fn output_changes_delimited(changes: &[diff::Change], delimiter: char) {
+    if let Err(e) = export::write_changes(std::io::stdout().lock(), changes, delimiter) {
+        eprintln!("{} cannot write changes: {}", "Error:".bright_red().bold(), e);
+        std::process::exit(1);
+    }
+}
+
+fn output_ndjson_line(result: &SiteResult) {
+    let mut stdout = std::io::stdout().lock();
+    let written = serde_json::to_writer(&mut stdout, result)
+        .map_err(std::io::Error::from)
+        .and_then(|()| writeln!(stdout))
+        .and_then(|()| stdout.flush());
+    if let Err(e) = written {
+        // The reader went away (e.g. `| head`); nothing left to stream to
+        if e.kind() == std::io::ErrorKind::BrokenPipe {
+            std::process::exit(0);
+        }
+        eprintln!("{} cannot write results: {}", "Error:".bright_red().bold(), e);
+        std::process::exit(1);
+    }
+}
+
+/// One username's share of a batch, as written by `--json`
+#[derive(Serialize)]
+struct UsernameGroup<'a> {
+    username: &'a str,
+    /// The input username this is a --permute variant of
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<&'a str>,
+    /// The --recurse links that led to this username
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    discovered_from: Vec<&'a Discovery>,
+    summary: Summary,
+    results: &'a [&'a SiteResult],
+}
+
+/// Count of results per status tag
+#[derive(Serialize, Default)]
+struct Summary {
+    found: usize,
+    not_found: usize,
+    rate_limited: usize,
+    challenge: usize,
+    blocked: usize,
+    dns_error: usize,
+    tls_error: usize,
+    invalid_username: usize,
+    unsupported: usize,
+    timeout: usize,
+    unavailable: usize,
+    error: usize,
+    total: usize,
+}
+
+impl Summary {
+    fn of(results: &[&SiteResult]) -> Self {
+        let mut summary = Summary { total: results.len(), ..Default::default() };
+        for result in results {
+            let count = match result.result {
+                CheckResult::Found => &mut summary.found,
+                CheckResult::NotFound => &mut summary.not_found,
+                CheckResult::RateLimited { .. } => &mut summary.rate_limited,
+                CheckResult::Challenge(_) => &mut summary.challenge,
+                CheckResult::Blocked(_) => &mut summary.blocked,
+                CheckResult::DnsError(_) => &mut summary.dns_error,
+                CheckResult::TlsError(_) => &mut summary.tls_error,
+                CheckResult::InvalidUsername(_) => &mut summary.invalid_username,
+                CheckResult::Unsupported(_) => &mut summary.unsupported,
+                CheckResult::Timeout => &mut summary.timeout,
+                CheckResult::Unavailable(_) => &mut summary.unavailable,
+                CheckResult::Error(_) => &mut summary.error,
+            };
+            *count += 1;
+        }
+        summary
+    }
+}
+
+/// Per-username JSON: `[{"username": ..., "summary": {...}, "results": [...]}]`
+fn output_json_grouped(groups: &[(&String, Vec<&SiteResult>)], discoveries: &[Discovery], clusters: Option<&[AvatarCluster]>) {
+    let json: Vec<UsernameGroup> = groups
+        .iter()
+        .map(|(username, results)| UsernameGroup {
+            username,
+            seed: results.first().and_then(|r| r.seed.as_deref()),
+            discovered_from: discoveries.iter().filter(|d| d.username.eq_ignore_ascii_case(username)).collect(),
+            summary: Summary::of(results),
+            results,
+        })
+        .collect();
+    print_json(&json, clusters);
+}
+
+/// Which account led to which, in the order the links were found
+fn output_discoveries(discoveries: &[Discovery]) {
+    println!("\n{}", "=".repeat(80).bright_white());
+    println!("{}", "Linked Accounts:".bright_white().bold());
+    for discovery in discoveries {
+        println!(
+            "  {} {} {} {} {}",
+            format!("{} on {}", discovery.from_username, discovery.from_site).bright_white(),
+            "→".bright_cyan(),
+            format!("{} on {}", discovery.username, discovery.site).bright_green(),
+            discovery.url.bright_blue().underline(),
+            format!("(depth {})", discovery.depth).bright_black()
+        );
+    }
+}
+
+/// Accounts whose avatars match, with --avatars
+fn output_avatar_clusters(clusters: &[AvatarCluster]) {
+    println!("\n{}", "=".repeat(80).bright_white());
+    println!("{}", "Likely Same Owner (matching avatars):".bright_white().bold());
+    if clusters.is_empty() {
+        println!("  {}", "No two found accounts share an avatar".bright_black());
+    }
+    for (index, cluster) in clusters.iter().enumerate() {
+        let closeness = if cluster.max_distance == 0 {
+            "identical pictures".to_string()
+        } else {
+            format!("within {} bits", cluster.max_distance)
+        };
+        println!("\n  {} {}", format!("Group {}", index + 1).bright_cyan().bold(), format!("({} accounts, {})", cluster.members.len(), closeness).bright_black());
+        for member in &cluster.members {
+            println!("    {} {} - {}", format!("{} on {}", member.username, member.site).bright_white(), member.url.bright_blue().underline(), member.avatar_url.bright_black());
+        }
+    }
+}
+
+/// Combined table across all usernames of a batch
+fn output_batch_summary(groups: &[(&String, Vec<&SiteResult>)]) {
+    let width = groups.iter().map(|(username, _)| username.chars().count()).max().unwrap_or(0).max(8);
+
+    println!("\n{}", "=".repeat(80).bright_white());
+    println!("{}", "Batch Summary:".bright_white().bold());
+    println!("  {:<width$}  {:>6}  {:>9}  {:>6}  {:>6}", "Username", "Found", "Not Found", "Errors", "Total", width = width);
+    for (username, results) in groups {
+        let found = results.iter().filter(|r| matches!(r.result, CheckResult::Found)).count();
+        let not_found = results.iter().filter(|r| matches!(r.result, CheckResult::NotFound)).count();
+        let errors = results.len() - found - not_found;
+        println!(
+            "  {:<width$}  {:>6}  {:>9}  {:>6}  {:>6}",
+            username,
+            found.to_string().bright_green().bold(),
+            not_found.to_string().bright_yellow(),
+            errors.to_string().bright_red(),
+            results.len(),
+            width = width
+        );
+    }
+}
+
+fn output_human_readable(args: &Args, results: &[&SiteResult]) {
+    // Filter and sort results
+    let mut found: Vec<&SiteResult> = results
+        .iter()
+        .copied()
+        .filter(|r| matches!(r.result, CheckResult::Found))
+        .collect();
+
+    let mut not_found: Vec<&SiteResult> = results
+        .iter()
+        .copied()
+        .filter(|r| matches!(r.result, CheckResult::NotFound))
+        .collect();
+
+    // Everything else: rate limits, challenges, network failures, unsupported sites...
+    let mut errors: Vec<&SiteResult> = results
+        .iter()
+        .copied()
+        .filter(|r| !r.result.is_conclusive())
+        .collect();
+
+    found.sort_by(|a, b| a.category.cmp(&b.category).then(a.site.cmp(&b.site)));
+    not_found.sort_by(|a, b| a.category.cmp(&b.category).then(a.site.cmp(&b.site)));
+    errors.sort_by(|a, b| a.result.tag().cmp(b.result.tag()).then(a.site.cmp(&b.site)));
+
+    let hidden = found.len() + not_found.len();
+    found.retain(|r| r.confidence >= args.min_confidence);
+    not_found.retain(|r| r.confidence >= args.min_confidence);
+    let hidden = hidden - found.len() - not_found.len();
+
     if !args.found_only {
         println!("\n{}", "=".repeat(80).bright_white());
     }
@@ -126,12 +1527,16 @@ fn output_human_readable(
         println!("{}", "=".repeat(80).bright_green());
 
         let mut current_category = String::new();
-        for result in found {
+        for result in &found {
             if result.category != current_category {
                 current_category = result.category.clone();
                 println!("\n{}", format!("[{}]", current_category).bright_cyan());
             }
-            println!("  {} {} - {}", "✓".bright_green(), result.site.bright_white(), result.url.bright_blue().underline());
+            println!("  {} {} - {} {}", "✓".bright_green(), result.site.bright_white(), result.url.bright_blue().underline(), format_confidence(result.confidence));
+            print_profile(result);
+            if args.verbose {
+                print_evidence(result);
+            }
         }
     } else {
         println!("\n{}", "✗ No accounts found".bright_red().bold());
@@ -147,12 +1552,13 @@ fn output_human_readable(
         println!("{}", "=".repeat(80).bright_yellow());
 
         let mut current_category = String::new();
-        for result in not_found {
+        for result in &not_found {
             if result.category != current_category {
                 current_category = result.category.clone();
                 println!("\n{}", format!("[{}]", current_category).bright_cyan());
             }
-            println!("  {} {} - {}", "✗".bright_yellow(), result.site.bright_white(), result.url.bright_blue().underline());
+            println!("  {} {} - {} {}", "✗".bright_yellow(), result.site.bright_white(), result.url.bright_blue().underline(), format_confidence(result.confidence));
+            print_evidence(result);
         }
     }
 
@@ -161,10 +1567,8 @@ fn output_human_readable(
         println!("\n{}", format!("⚠ ERRORS ({})", errors.len()).bright_red().bold());
         println!("{}", "=".repeat(80).bright_red());
 
-        for result in errors {
-            if let checker::CheckResult::Error(e) = &result.result {
-                println!("  {} {}: {}", "⚠".bright_red(), result.site.bright_white(), e.bright_black());
-            }
+        for result in &errors {
+            println!("  {} {}: {}", "⚠".bright_red(), result.site.bright_white(), result.result.to_string().bright_black());
         }
     }
 
@@ -174,6 +1578,76 @@ fn output_human_readable(
     println!("  {}: {}", "Found".bright_green(), found.len().to_string().bright_green().bold());
     println!("  {}: {}", "Not Found".bright_yellow(), not_found.len().to_string().bright_yellow());
     println!("  {}: {}", "Errors".bright_red(), errors.len().to_string().bright_red());
+    for (tag, label) in ERROR_KINDS {
+        let count = errors.iter().filter(|r| r.result.tag() == tag).count();
+        println!("    {}: {}", label.bright_red(), count.to_string().bright_red());
+    }
     println!("  {}: {}", "Total".bright_white(), (found.len() + not_found.len() + errors.len()).to_string().bright_white());
+    if hidden > 0 {
+        println!("  {}: {}", format!("Below {:.0}% confidence (hidden)", args.min_confidence * 100.0).bright_black(), hidden.to_string().bright_black());
+    }
 }
 
+fn format_confidence(confidence: f32) -> ColoredString {
+    let text = format!("({:.0}%)", confidence * 100.0);
+    if confidence >= 0.8 {
+        text.bright_green()
+    } else if confidence >= 0.5 {
+        text.bright_yellow()
+    } else {
+        text.bright_red()
+    }
+}
+
+fn print_profile(result: &SiteResult) {
+    let Some(profile) = &result.profile else {
+        return;
+    };
+    let fields = [
+        ("Name", &profile.display_name),
+        ("Bio", &profile.bio),
+        ("Location", &profile.location),
+        ("Joined", &profile.joined),
+        ("Avatar", &profile.avatar_url),
+    ];
+    for (label, value) in fields {
+        if let Some(value) = value {
+            println!("      {} {}", format!("{}:", label).bright_black(), value);
+        }
+    }
+    for link in &profile.links {
+        println!("      {} {}", "Link:".bright_black(), link.bright_blue());
+    }
+}
+
+fn print_evidence(result: &SiteResult) {
+    for evidence in &result.evidence {
+        println!("      {} {}", "·".bright_black(), evidence.detail.bright_black());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intervals_parse_in_every_unit() {
+        assert_eq!(parse_interval("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_interval("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_interval("30m"), Ok(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_interval(" 6h "), Ok(Duration::from_secs(6 * 60 * 60)));
+        assert_eq!(parse_interval("1d"), Ok(Duration::from_secs(24 * 60 * 60)));
+        assert_eq!(format_interval(parse_interval("1d").unwrap()), "1d");
+    }
+
+    #[test]
+    fn bad_intervals_are_rejected() {
+        assert!(parse_interval("0h").unwrap_err().contains("longer than zero"));
+        assert!(parse_interval("6w").unwrap_err().contains("unknown interval unit"));
+        assert!(parse_interval("soon").unwrap_err().contains("not an interval"));
+        assert!(parse_interval("99999999999999999999d").unwrap_err().contains("not an interval"));
+        // Fits in a u64 as a count, but not once turned into seconds
+        assert_eq!(parse_interval("18446744073709551615m"), Err("interval `18446744073709551615m` is too long".to_string()));
+        assert!(parse_interval("213503982334602d").unwrap_err().contains("too long"));
+    }
+}
@@ -0,0 +1,176 @@
+//! Change notifications for `vidocq watch`
+
+use crate::diff::Change;
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+
+/// Where to send notifications
+#[derive(Debug, Clone)]
+pub enum Notifier {
+    /// POST the notification as JSON to this URL
+    Webhook(String),
+    /// Run this command through `sh -c` with the notification JSON on stdin
+    Command(String),
+    /// Append the notification to this file as one JSON line
+    File(PathBuf),
+}
+
+/// What changed in one watch run
+#[derive(Debug, Serialize)]
+pub struct Notification<'a> {
+    pub usernames: &'a [String],
+    /// HTTP date of the run that found the changes
+    pub checked_at: String,
+    pub changes: &'a [Change],
+}
+
+impl Notifier {
+    pub async fn send(&self, client: &reqwest::Client, notification: &Notification<'_>) -> Result<(), String> {
+        let json = serde_json::to_string(notification).map_err(|e| e.to_string())?;
+        match self {
+            Notifier::Webhook(url) => {
+                let response = client
+                    .post(url)
+                    .header("Content-Type", "application/json")
+                    .body(json)
+                    .send()
+                    .await
+                    .map_err(|e| format!("webhook {}: {}", url, e))?;
+                if !response.status().is_success() {
+                    return Err(format!("webhook {} answered HTTP {}", url, response.status().as_u16()));
+                }
+                Ok(())
+            }
+            Notifier::Command(command) => {
+                let mut child = tokio::process::Command::new("sh")
+                    .arg("-c")
+                    .arg(command)
+                    .env("VIDOCQ_CHANGES", notification.changes.len().to_string())
+                    .stdin(Stdio::piped())
+                    .spawn()
+                    .map_err(|e| format!("cannot run `{}`: {}", command, e))?;
+                if let Some(mut stdin) = child.stdin.take() {
+                    // A command that ignores its input may close stdin early; that's fine
+                    let _ = stdin.write_all(json.as_bytes()).await;
+                    let _ = stdin.write_all(b"\n").await;
+                }
+                let status = child.wait().await.map_err(|e| format!("`{}`: {}", command, e))?;
+                if !status.success() {
+                    return Err(format!("`{}` exited with {}", command, status));
+                }
+                Ok(())
+            }
+            Notifier::File(path) => {
+                let mut file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| format!("cannot open {}: {}", path.display(), e))?;
+                writeln!(file, "{}", json).map_err(|e| format!("cannot write {}: {}", path.display(), e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::ChangeKind;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    /// A webhook receiver that answers every request with `status` and hands back what it received
+    async fn webhook(status: u16) -> (String, tokio::sync::mpsc::UnboundedReceiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (sender, received) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                // Read the head, then as much body as Content-Length announces
+                loop {
+                    let n = socket.read(&mut buf).await.unwrap_or(0);
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request);
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = head
+                            .lines()
+                            .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse().unwrap_or(0)))
+                            .unwrap_or(0);
+                        if body.len() >= length {
+                            break;
+                        }
+                    }
+                }
+                let _ = sender.send(String::from_utf8_lossy(&request).into_owned());
+                let response = format!("HTTP/1.1 {} Whatever\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        (url, received)
+    }
+
+    fn change() -> Change {
+        Change {
+            username: "alice".to_string(),
+            site: "GitHub".to_string(),
+            category: "Coding".to_string(),
+            url: "https://github.com/alice".to_string(),
+            change: ChangeKind::Appeared,
+            old: Some("not_found".to_string()),
+            new: Some("found".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn webhooks_receive_the_notification_as_json() {
+        let (url, mut received) = webhook(204).await;
+        let usernames = ["alice".to_string()];
+        let changes = [change()];
+        let notification = Notification { usernames: &usernames, checked_at: "Sat, 17 Oct 2026 09:12:44 GMT".to_string(), changes: &changes };
+
+        Notifier::Webhook(url).send(&reqwest::Client::new(), &notification).await.unwrap();
+
+        let request = received.recv().await.unwrap();
+        let (head, body) = request.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("POST /hook HTTP/1.1\r\n"), "{}", head);
+        assert!(head.to_ascii_lowercase().contains("content-type: application/json"), "{}", head);
+        let json: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(json["usernames"], serde_json::json!(["alice"]));
+        assert_eq!(json["checked_at"], "Sat, 17 Oct 2026 09:12:44 GMT");
+        assert_eq!(json["changes"][0]["site"], "GitHub");
+        assert_eq!(json["changes"][0]["change"], "appeared");
+        assert_eq!(json["changes"][0]["new"], "found");
+    }
+
+    #[tokio::test]
+    async fn webhook_errors_are_reported() {
+        let (url, _received) = webhook(500).await;
+        let notification = Notification { usernames: &[], checked_at: String::new(), changes: &[] };
+        let error = Notifier::Webhook(url.clone()).send(&reqwest::Client::new(), &notification).await.unwrap_err();
+        assert_eq!(error, format!("webhook {} answered HTTP 500", url));
+    }
+
+    #[tokio::test]
+    async fn files_get_one_line_per_notification() {
+        let path = std::env::temp_dir().join(format!("vidocq-notify-{}.ndjson", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let changes = [change()];
+        let notification = Notification { usernames: &[], checked_at: String::new(), changes: &changes };
+        let notifier = Notifier::File(path.clone());
+        notifier.send(&reqwest::Client::new(), &notification).await.unwrap();
+        notifier.send(&reqwest::Client::new(), &notification).await.unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.lines().all(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["changes"][0]["site"] == "GitHub"));
+    }
+}
@@ -0,0 +1,198 @@
+//! Username variant generation for alias discovery (`--permute`)
+
+/// Separators people put between name parts
+const SEPARATORS: [&str; 4] = ["", "_", ".", "-"];
+/// Common numeric suffixes, most popular first
+const SUFFIXES: [&str; 6] = ["1", "2", "01", "123", "00", "99"];
+/// Leetspeak substitutions, applied one letter class at a time and then all together
+const LEET: [(char, char); 6] = [('o', '0'), ('e', '3'), ('i', '1'), ('a', '4'), ('s', '5'), ('t', '7')];
+
+#[derive(Debug, Clone)]
+pub struct PermuteOptions {
+    /// First name, for first/last combinations
+    pub first: Option<String>,
+    /// Last name, for first/last combinations
+    pub last: Option<String>,
+    /// Upper bound on variants per seed, including the seed itself
+    pub max_variants: usize,
+}
+
+impl Default for PermuteOptions {
+    fn default() -> Self {
+        Self { first: None, last: None, max_variants: 50 }
+    }
+}
+
+/// Expand a seed username into a bounded, deduplicated list of variants.
+/// The seed always comes first; the rest are ordered from most to least likely.
+pub fn permutations(seed: &str, options: &PermuteOptions) -> Vec<String> {
+    let mut variants = Variants::new(options.max_variants);
+    variants.push(seed.to_string());
+
+    // Name parts: the seed split on separators, or the given first/last names
+    let parts = split_parts(seed);
+    let first = options.first.as_deref().map(normalise).filter(|s| !s.is_empty());
+    let last = options.last.as_deref().map(normalise).filter(|s| !s.is_empty());
+
+    // Separator variants of the seed's own parts: john_doe -> johndoe, john.doe, john-doe
+    if parts.len() > 1 {
+        for separator in SEPARATORS {
+            variants.push(parts.join(separator));
+        }
+    }
+
+    // First/last name combinations: johndoe, john.doe, doejohn, jdoe, johnd, j.doe
+    if let (Some(first), Some(last)) = (&first, &last) {
+        let initial = |s: &str| s.chars().next().map(String::from).unwrap_or_default();
+        for separator in SEPARATORS {
+            variants.push(format!("{}{}{}", first, separator, last));
+        }
+        for separator in SEPARATORS {
+            variants.push(format!("{}{}{}", last, separator, first));
+        }
+        for separator in SEPARATORS {
+            variants.push(format!("{}{}{}", initial(first), separator, last));
+        }
+        variants.push(format!("{}{}", first, initial(last)));
+        variants.push(format!("{}{}", last, initial(first)));
+    } else if let Some(name) = first.as_ref().or(last.as_ref()) {
+        variants.push(name.clone());
+    }
+
+    // Bases for leetspeak and suffixes: the compact forms generated so far
+    let compact: Vec<String> = variants.list.iter()
+        .filter(|v| !v.contains(['_', '.', '-']))
+        .cloned()
+        .collect();
+
+    // Each base gets its suffixes and leetspeak forms before the next base is considered,
+    // so the bound trims the least likely variants first
+    for base in &compact {
+        for suffix in SUFFIXES {
+            variants.push(format!("{}{}", base, suffix));
+        }
+        for (from, to) in LEET {
+            if base.contains(from) {
+                variants.push(base.replace(from, &to.to_string()));
+            }
+        }
+        variants.push(base.chars().map(leet_char).collect());
+    }
+
+    variants.list
+}
+
+/// Split a username into its name parts on separators and camelCase boundaries
+fn split_parts(username: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in username.chars() {
+        if matches!(c, '_' | '.' | '-') {
+            if !current.is_empty() {
+                parts.push(std::mem::take(&mut current));
+            }
+            previous_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && previous_lower && !current.is_empty() {
+            parts.push(std::mem::take(&mut current));
+        }
+        previous_lower = c.is_lowercase();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+fn normalise(name: &str) -> String {
+    name.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+fn leet_char(c: char) -> char {
+    LEET.iter().find(|(from, _)| *from == c).map(|(_, to)| *to).unwrap_or(c)
+}
+
+/// Insertion-ordered, bounded list, deduplicated case-insensitively since most sites are
+/// case-insensitive about usernames
+struct Variants {
+    list: Vec<String>,
+    max: usize,
+}
+
+impl Variants {
+    fn new(max: usize) -> Self {
+        Self { list: Vec::new(), max: max.max(1) }
+    }
+
+    fn push(&mut self, variant: String) {
+        if self.list.len() < self.max && !variant.is_empty() && !self.list.iter().any(|v| v.eq_ignore_ascii_case(&variant)) {
+            self.list.push(variant);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seed_comes_first_with_separator_suffix_and_leet_variants() {
+        let variants = permutations("john_doe", &PermuteOptions::default());
+        assert_eq!(variants[0], "john_doe");
+        for expected in ["johndoe", "john.doe", "john-doe", "johndoe1", "johndoe123", "j0hnd0e", "j0hnd03"] {
+            assert!(variants.contains(&expected.to_string()), "missing {}: {:?}", expected, variants);
+        }
+        assert!(variants.len() <= 50);
+    }
+
+    #[test]
+    fn variants_are_deduplicated_case_insensitively() {
+        let variants = permutations("JohnDoe", &PermuteOptions::default());
+        assert_eq!(variants[0], "JohnDoe");
+        assert!(!variants.contains(&"johndoe".to_string()), "{:?}", variants);
+        assert!(variants.contains(&"john_doe".to_string()));
+    }
+
+    #[test]
+    fn max_variants_bounds_the_list_and_keeps_the_seed() {
+        for max in [1, 3, 10] {
+            let variants = permutations("john_doe", &PermuteOptions { max_variants: max, ..PermuteOptions::default() });
+            assert_eq!(variants.len(), max);
+            assert_eq!(variants[0], "john_doe");
+        }
+        let zero = permutations("john_doe", &PermuteOptions { max_variants: 0, ..PermuteOptions::default() });
+        assert_eq!(zero, vec!["john_doe".to_string()]);
+    }
+
+    #[test]
+    fn first_and_last_names_are_combined() {
+        let options = PermuteOptions {
+            first: Some("John".to_string()),
+            last: Some("O'Doe".to_string()),
+            max_variants: 100,
+        };
+        let variants = permutations("jd", &options);
+        for expected in ["johnodoe", "john.odoe", "odoejohn", "jodoe", "j.odoe", "johno", "odoej"] {
+            assert!(variants.contains(&expected.to_string()), "missing {}: {:?}", expected, variants);
+        }
+    }
+
+    #[test]
+    fn a_single_name_is_added_as_is() {
+        let options = PermuteOptions { first: Some("Jane".to_string()), ..PermuteOptions::default() };
+        let variants = permutations("jd", &options);
+        assert_eq!(&variants[..2], ["jd", "jane"]);
+        assert!(variants.contains(&"jane1".to_string()));
+    }
+
+    #[test]
+    fn seeds_split_on_separators_and_camel_case() {
+        assert_eq!(split_parts("john_doe"), ["john", "doe"]);
+        assert_eq!(split_parts("JohnDoe"), ["john", "doe"]);
+        assert_eq!(split_parts("john..doe-jr"), ["john", "doe", "jr"]);
+        assert_eq!(split_parts("JOHN"), ["john"]);
+    }
+}
@@ -0,0 +1,308 @@
+//! Following links between accounts (`--recurse`)
+//!
+//! A found profile often links to the same person elsewhere: a GitHub bio pointing at a Twitter
+//! handle, a Keybase proof, a Linktree. [`LinkResolver`] reverses the catalogue's URL templates to
+//! turn those links back into (site, username) pairs, so the usernames can be scanned in turn.
+
+use crate::checker::{CheckResult, SiteResult};
+use crate::sites::Site;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+/// http(s) URLs in free text
+static URL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"https?://[^\s<>"'()\[\]]+"#).expect("static regex"));
+
+/// One edge of the discovery graph: a found account whose profile links to another catalogue account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Discovery {
+    /// The found account the link was read from
+    pub from_username: String,
+    pub from_site: String,
+    /// The link as it appeared on the profile
+    pub url: String,
+    /// The catalogue site and username the link points to
+    pub site: String,
+    pub username: String,
+    /// Scan round that found the link: 1 for links on the original usernames' profiles
+    pub depth: u32,
+}
+
+struct Template {
+    site: String,
+    pattern: Regex,
+    username_pattern: Option<Regex>,
+    /// Length of the literal parts of the URL, so the most specific template wins
+    specificity: usize,
+}
+
+/// Maps profile URLs back to the catalogue site and username they belong to
+pub struct LinkResolver {
+    templates: Vec<Template>,
+}
+
+impl LinkResolver {
+    pub fn new(sites: &[Site]) -> Self {
+        let templates = sites.iter().filter_map(template).collect();
+        Self { templates }
+    }
+
+    /// The (site, username) a URL points to, if it is a profile URL of a catalogue site.
+    /// The most specific template wins, and the earlier catalogue entry on a tie.
+    pub fn resolve(&self, url: &str) -> Option<(&str, String)> {
+        self.templates
+            .iter()
+            // max_by_key keeps the last of equals
+            .rev()
+            .filter_map(|template| {
+                let username = template.pattern.captures(url)?.name("username")?.as_str();
+                if template.username_pattern.as_ref().is_some_and(|pattern| !pattern.is_match(username)) {
+                    return None;
+                }
+                Some((template, username))
+            })
+            .max_by_key(|(template, _)| template.specificity)
+            .map(|(template, username)| (template.site.as_str(), username.to_string()))
+    }
+
+    /// Links from a found account's extracted profile (its links and any URLs in its bio)
+    /// that point to another catalogue account
+    pub fn discover(&self, result: &SiteResult, depth: u32) -> Vec<Discovery> {
+        let Some(profile) = result.profile.as_ref().filter(|_| result.result == CheckResult::Found) else {
+            return Vec::new();
+        };
+        let bio_urls = profile.bio.as_deref().map(urls_in).unwrap_or_default();
+
+        let mut discoveries: Vec<Discovery> = Vec::new();
+        for url in profile.links.iter().map(String::as_str).chain(bio_urls) {
+            let Some((site, username)) = self.resolve(url) else {
+                continue;
+            };
+            // The profile linking to itself, e.g. a canonical URL
+            if site == result.site && username.eq_ignore_ascii_case(&result.username) {
+                continue;
+            }
+            if discoveries.iter().any(|d| d.site == site && d.username.eq_ignore_ascii_case(&username)) {
+                continue;
+            }
+            discoveries.push(Discovery {
+                from_username: result.username.clone(),
+                from_site: result.site.clone(),
+                url: url.to_string(),
+                site: site.to_string(),
+                username,
+                depth,
+            });
+        }
+        discoveries
+    }
+}
+
+/// Turn `https://www.example.com/users/{}/` into a regex capturing the username. The scheme,
+/// a leading `www.`, a trailing slash, a query string and a fragment are all optional.
+fn template(site: &Site) -> Option<Template> {
+    let url = site.url.split_once("://").map_or(site.url.as_str(), |(_, rest)| rest);
+    let url = url.strip_prefix("www.").unwrap_or(url);
+    let (prefix, suffix) = url.split_once("{}")?;
+    let suffix = suffix.trim_end_matches('/');
+    // Usernames in a host name can't span dots
+    let username = if prefix.contains('/') { r"[^/?#&]+" } else { r"[^/?#&.]+" };
+    let pattern = format!(
+        r"(?i)^https?://(?:www\.)?{}(?P<username>{}){}/?(?:[?#].*)?$",
+        regex::escape(prefix),
+        username,
+        regex::escape(suffix)
+    );
+    Some(Template {
+        site: site.name.clone(),
+        pattern: Regex::new(&pattern).ok()?,
+        username_pattern: site.username_pattern.as_deref().and_then(|p| Regex::new(p).ok()),
+        specificity: prefix.len() + suffix.len(),
+    })
+}
+
+/// http(s) URLs mentioned in free text, without trailing punctuation
+fn urls_in(text: &str) -> Vec<&str> {
+    URL.find_iter(text).map(|m| m.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?'])).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::Profile;
+    use crate::sites::{parse_manifest, ManifestFormat};
+
+    const CATALOGUE: &str = r#"
+[[site]]
+name = "GitHub"
+url = "https://github.com/{}"
+category = "Coding"
+username_pattern = "^[A-Za-z0-9-]{1,39}$"
+
+[[site]]
+name = "Tumblr"
+url = "https://{}.tumblr.com/"
+category = "Blog"
+
+[[site]]
+name = "Board"
+url = "https://www.board.example/users/{}/"
+category = "Forum"
+
+[[site]]
+name = "Social"
+url = "https://social.example/{}"
+category = "Social"
+
+[[site]]
+name = "Social Handle"
+url = "https://social.example/@{}"
+category = "Social"
+
+[[site]]
+name = "Wiki"
+url = "https://wiki.example/u/{}"
+category = "Wiki"
+
+[[site]]
+name = "Wiki Mirror"
+url = "https://wiki.example/u/{}"
+category = "Wiki"
+"#;
+
+    fn resolver() -> LinkResolver {
+        LinkResolver::new(&parse_manifest(CATALOGUE, ManifestFormat::Toml).unwrap())
+    }
+
+    fn resolved(resolver: &LinkResolver, url: &str) -> Option<(String, String)> {
+        resolver.resolve(url).map(|(site, username)| (site.to_string(), username))
+    }
+
+    fn pair(site: &str, username: &str) -> Option<(String, String)> {
+        Some((site.to_string(), username.to_string()))
+    }
+
+    #[test]
+    fn path_templates_resolve() {
+        let resolver = resolver();
+        assert_eq!(resolved(&resolver, "https://github.com/alice"), pair("GitHub", "alice"));
+        assert_eq!(resolved(&resolver, "http://github.com/Alice"), pair("GitHub", "Alice"));
+        assert_eq!(resolved(&resolver, "https://GitHub.com/alice"), pair("GitHub", "alice"));
+        // Deeper paths are something else than the profile
+        assert_eq!(resolved(&resolver, "https://github.com/alice/dotfiles"), None);
+        assert_eq!(resolved(&resolver, "https://github.com/"), None);
+        assert_eq!(resolved(&resolver, "https://gitlab.com/alice"), None);
+    }
+
+    #[test]
+    fn www_trailing_slashes_queries_and_fragments_are_optional() {
+        let resolver = resolver();
+        for url in [
+            "https://www.github.com/alice",
+            "https://github.com/alice/",
+            "https://github.com/alice?tab=repositories",
+            "https://github.com/alice/?tab=repositories",
+            "https://github.com/alice#readme",
+        ] {
+            assert_eq!(resolved(&resolver, url), pair("GitHub", "alice"), "{}", url);
+        }
+        // The catalogue's own www. and trailing slash are optional too
+        assert_eq!(resolved(&resolver, "https://board.example/users/alice"), pair("Board", "alice"));
+        assert_eq!(resolved(&resolver, "https://www.board.example/users/alice/"), pair("Board", "alice"));
+    }
+
+    #[test]
+    fn subdomain_templates_resolve() {
+        let resolver = resolver();
+        assert_eq!(resolved(&resolver, "https://alice.tumblr.com"), pair("Tumblr", "alice"));
+        assert_eq!(resolved(&resolver, "https://alice.tumblr.com/"), pair("Tumblr", "alice"));
+        assert_eq!(resolved(&resolver, "https://www.alice.tumblr.com/?og=1"), pair("Tumblr", "alice"));
+        // A username in a host name can't span dots
+        assert_eq!(resolved(&resolver, "https://static.alice.tumblr.com/"), None);
+        assert_eq!(resolved(&resolver, "https://alice.tumblr.com/post/1"), None);
+    }
+
+    #[test]
+    fn the_most_specific_template_wins() {
+        let resolver = resolver();
+        // Both Social templates match; the one with the literal @ is more specific
+        assert_eq!(resolved(&resolver, "https://social.example/@alice"), pair("Social Handle", "alice"));
+        assert_eq!(resolved(&resolver, "https://social.example/alice"), pair("Social", "alice"));
+        // Equally specific: the earlier catalogue entry
+        assert_eq!(resolved(&resolver, "https://wiki.example/u/alice"), pair("Wiki", "alice"));
+    }
+
+    #[test]
+    fn usernames_must_fit_the_sites_pattern() {
+        let resolver = resolver();
+        assert_eq!(resolved(&resolver, "https://github.com/alice_smith"), None);
+        assert_eq!(resolved(&resolver, "https://github.com/alice-smith"), pair("GitHub", "alice-smith"));
+    }
+
+    fn found(site: &str, username: &str, profile: Profile) -> SiteResult {
+        SiteResult {
+            username: username.to_string(),
+            seed: None,
+            site: site.to_string(),
+            url: format!("https://github.com/{}", username),
+            category: "Coding".to_string(),
+            result: CheckResult::Found,
+            status_code: Some(200),
+            final_url: None,
+            attempts: 1,
+            confidence: 0.9,
+            evidence: Vec::new(),
+            profile: Some(profile),
+        }
+    }
+
+    #[test]
+    fn discover_reads_links_and_bio_urls() {
+        let profile = Profile {
+            links: vec![
+                "https://alice.tumblr.com/".to_string(),
+                // Itself, in another case and with a trailing slash
+                "https://github.com/Alice/".to_string(),
+                "https://example.org/not-in-the-catalogue".to_string(),
+            ],
+            bio: Some("Mostly at https://social.example/@alice. Old blog: https://ALICE.tumblr.com, board (https://board.example/users/al1ce)!".to_string()),
+            ..Default::default()
+        };
+        let discoveries = resolver().discover(&found("GitHub", "alice", profile), 2);
+        let pairs: Vec<(&str, &str, &str)> = discoveries.iter().map(|d| (d.site.as_str(), d.username.as_str(), d.url.as_str())).collect();
+        assert_eq!(
+            pairs,
+            [
+                ("Tumblr", "alice", "https://alice.tumblr.com/"),
+                ("Social Handle", "alice", "https://social.example/@alice"),
+                ("Board", "al1ce", "https://board.example/users/al1ce"),
+            ]
+        );
+        for discovery in &discoveries {
+            assert_eq!((discovery.from_site.as_str(), discovery.from_username.as_str(), discovery.depth), ("GitHub", "alice", 2));
+        }
+    }
+
+    #[test]
+    fn only_found_profiles_are_read() {
+        let resolver = resolver();
+        let profile = Profile { links: vec!["https://alice.tumblr.com/".to_string()], ..Default::default() };
+        let mut result = found("GitHub", "alice", profile);
+        assert_eq!(resolver.discover(&result, 1).len(), 1);
+
+        result.result = CheckResult::NotFound;
+        assert!(resolver.discover(&result, 1).is_empty());
+        result.result = CheckResult::Found;
+        result.profile = None;
+        assert!(resolver.discover(&result, 1).is_empty());
+    }
+
+    #[test]
+    fn urls_in_text_lose_trailing_punctuation() {
+        assert_eq!(
+            urls_in("see https://a.example/x, (http://b.example/y) and \"https://c.example/?q=1\"; ok?"),
+            ["https://a.example/x", "http://b.example/y", "https://c.example/?q=1"]
+        );
+        assert!(urls_in("no links, just ftp://a.example").is_empty());
+    }
+}
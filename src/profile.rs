@@ -0,0 +1,365 @@
+//! Profile metadata extraction from found pages (`--extract`)
+
+use crate::sites::ProfileSelectors;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// What a profile page says about its owner. Every field is optional; sources are tried in order
+/// per-site selectors, then JSON-LD, then Open Graph tags.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bio: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar_url: Option<String>,
+    /// Perceptual hash of the avatar as 16 hex digits, when avatars are hashed (`--avatars`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// External links: selector matches, JSON-LD `sameAs` and `rel="me"` links, absolute and deduplicated
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub joined: Option<String>,
+}
+
+impl Profile {
+    pub fn is_empty(&self) -> bool {
+        *self == Profile::default()
+    }
+
+    /// Fill the fields still missing from `other`
+    fn fill_from(&mut self, other: Profile) {
+        self.display_name = self.display_name.take().or(other.display_name);
+        self.bio = self.bio.take().or(other.bio);
+        self.avatar_url = self.avatar_url.take().or(other.avatar_url);
+        self.location = self.location.take().or(other.location);
+        self.joined = self.joined.take().or(other.joined);
+        for link in other.links {
+            if !self.links.contains(&link) {
+                self.links.push(link);
+            }
+        }
+    }
+}
+
+/// Extract profile metadata from a page fetched from `page_url`
+pub fn extract(body: &str, page_url: &str, selectors: &ProfileSelectors) -> Profile {
+    let document = Html::parse_document(body);
+    let base = Url::parse(page_url).ok();
+
+    let mut profile = from_selectors(&document, selectors, base.as_ref());
+    profile.fill_from(from_json_ld(&document, base.as_ref()));
+    profile.fill_from(from_open_graph(&document, base.as_ref()));
+
+    // rel="me" links are how Mastodon, GitHub and many blogs mark the owner's other accounts
+    if let Ok(rel_me) = Selector::parse(r#"a[rel~="me"], link[rel~="me"]"#) {
+        let links = document.select(&rel_me).filter_map(|e| e.value().attr("href")).filter_map(|href| absolute(href, base.as_ref()));
+        profile.fill_from(Profile { links: links.collect(), ..Default::default() });
+    }
+
+    // A profile linking to itself isn't an external link
+    profile.links.retain(|link| link.trim_end_matches('/') != page_url.trim_end_matches('/'));
+    profile
+}
+
+fn from_selectors(document: &Html, selectors: &ProfileSelectors, base: Option<&Url>) -> Profile {
+    let first = |selector: &Option<String>, attr: &str| -> Option<String> {
+        let selector = Selector::parse(selector.as_deref()?).ok()?;
+        document.select(&selector).find_map(|element| value_of(element, attr))
+    };
+    let links = selectors
+        .links
+        .as_deref()
+        .and_then(|s| Selector::parse(s).ok())
+        .map(|selector| {
+            document
+                .select(&selector)
+                .filter_map(|e| e.value().attr("href"))
+                .filter_map(|href| absolute(href, base))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Profile {
+        display_name: first(&selectors.display_name, ""),
+        bio: first(&selectors.bio, ""),
+        avatar_url: first(&selectors.avatar, "src").and_then(|src| absolute(&src, base)),
+        location: first(&selectors.location, ""),
+        links: dedup(links),
+        joined: first(&selectors.joined, "datetime"),
+        ..Default::default()
+    }
+}
+
+/// An element's value: its `content` attribute (meta tags), then `attr` if given, then its text
+fn value_of(element: ElementRef, attr: &str) -> Option<String> {
+    let value = element
+        .value()
+        .attr("content")
+        .or_else(|| if attr.is_empty() { None } else { element.value().attr(attr) })
+        .map(str::to_string)
+        .unwrap_or_else(|| element.text().collect::<Vec<_>>().join(" "));
+    clean(&value)
+}
+
+fn from_open_graph(document: &Html, base: Option<&Url>) -> Profile {
+    let meta = |property: &str| -> Option<String> {
+        let selector = Selector::parse(&format!(r#"meta[property="{0}"], meta[name="{0}"]"#, property)).ok()?;
+        document.select(&selector).find_map(|e| e.value().attr("content")).and_then(clean)
+    };
+    Profile {
+        display_name: meta("og:title").or_else(|| meta("twitter:title")),
+        bio: meta("og:description").or_else(|| meta("twitter:description")),
+        avatar_url: meta("og:image").or_else(|| meta("twitter:image")).and_then(|src| absolute(&src, base)),
+        ..Default::default()
+    }
+}
+
+fn from_json_ld(document: &Html, base: Option<&Url>) -> Profile {
+    let Ok(selector) = Selector::parse(r#"script[type="application/ld+json"]"#) else {
+        return Profile::default();
+    };
+    let mut profile = Profile::default();
+    for script in document.select(&selector) {
+        let Ok(json) = serde_json::from_str::<Value>(&script.text().collect::<String>()) else {
+            continue;
+        };
+        let mut nodes = Vec::new();
+        collect_nodes(&json, &mut nodes);
+        for node in nodes {
+            // A ProfilePage describes its Person in mainEntity, but carries the creation date itself
+            let joined = text(node.get("dateCreated"));
+            let person = if has_type(node, "ProfilePage") { node.get("mainEntity").unwrap_or(node) } else { node };
+            if !has_type(person, "Person") && !has_type(person, "Organization") {
+                continue;
+            }
+            let links = match person.get("sameAs") {
+                Some(Value::Array(items)) => items.iter().filter_map(|v| v.as_str()).filter_map(|s| absolute(s, base)).collect(),
+                Some(Value::String(s)) => absolute(s, base).into_iter().collect(),
+                _ => Vec::new(),
+            };
+            profile.fill_from(Profile {
+                display_name: text(person.get("name")).or_else(|| text(person.get("alternateName"))),
+                bio: text(person.get("description")),
+                avatar_url: image_url(person.get("image")).and_then(|src| absolute(&src, base)),
+                location: place(person.get("homeLocation")).or_else(|| place(person.get("address"))),
+                links,
+                joined: joined.or_else(|| text(person.get("dateCreated"))),
+                ..Default::default()
+            });
+        }
+    }
+    profile
+}
+
+/// Every JSON-LD node, looking inside arrays and `@graph`
+fn collect_nodes<'a>(value: &'a Value, nodes: &mut Vec<&'a Value>) {
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| collect_nodes(item, nodes)),
+        Value::Object(map) => {
+            nodes.push(value);
+            if let Some(graph) = map.get("@graph") {
+                collect_nodes(graph, nodes);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn has_type(node: &Value, wanted: &str) -> bool {
+    match node.get("@type") {
+        Some(Value::String(t)) => t == wanted,
+        Some(Value::Array(types)) => types.iter().any(|t| t.as_str() == Some(wanted)),
+        _ => false,
+    }
+}
+
+fn text(value: Option<&Value>) -> Option<String> {
+    value?.as_str().and_then(clean)
+}
+
+fn image_url(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(s) => clean(s),
+        Value::Object(map) => text(map.get("url").or_else(|| map.get("contentUrl"))),
+        Value::Array(items) => image_url(items.first()),
+        _ => None,
+    }
+}
+
+fn place(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(s) => clean(s),
+        Value::Object(map) => text(map.get("name")).or_else(|| {
+            let parts: Vec<String> = ["addressLocality", "addressRegion", "addressCountry"]
+                .iter()
+                .filter_map(|key| text(map.get(*key)))
+                .collect();
+            (!parts.is_empty()).then(|| parts.join(", "))
+        }),
+        _ => None,
+    }
+}
+
+/// Collapse whitespace; empty strings become `None`
+fn clean(value: &str) -> Option<String> {
+    let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!value.is_empty()).then_some(value)
+}
+
+/// Resolve a possibly relative http(s) URL against the page
+fn absolute(href: &str, base: Option<&Url>) -> Option<String> {
+    let href = href.trim();
+    let url = match base {
+        Some(base) => base.join(href).ok()?,
+        None => Url::parse(href).ok()?,
+    };
+    matches!(url.scheme(), "http" | "https").then(|| url.to_string())
+}
+
+fn dedup(links: Vec<String>) -> Vec<String> {
+    let mut unique = Vec::new();
+    for link in links {
+        if !unique.contains(&link) {
+            unique.push(link);
+        }
+    }
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE_URL: &str = "https://example.com/users/alice";
+
+    fn page(head: &str, body: &str) -> String {
+        format!("<html><head>{}</head><body>{}</body></html>", head, body)
+    }
+
+    #[test]
+    fn open_graph_tags_fill_the_basics() {
+        let html = page(
+            r#"<meta property="og:title" content="  Alice   Smith ">
+               <meta property="og:description" content="Writes things">
+               <meta property="og:image" content="/avatars/alice.png">
+               <meta name="twitter:title" content="Ignored, og:title comes first">"#,
+            "",
+        );
+        let profile = extract(&html, PAGE_URL, &ProfileSelectors::default());
+        assert_eq!(profile.display_name.as_deref(), Some("Alice Smith"));
+        assert_eq!(profile.bio.as_deref(), Some("Writes things"));
+        assert_eq!(profile.avatar_url.as_deref(), Some("https://example.com/avatars/alice.png"));
+        assert_eq!(profile.location, None);
+    }
+
+    #[test]
+    fn twitter_tags_stand_in_for_open_graph() {
+        let html = page(r#"<meta name="twitter:title" content="Alice"><meta name="twitter:image" content="https://cdn.example.com/a.jpg">"#, "");
+        let profile = extract(&html, PAGE_URL, &ProfileSelectors::default());
+        assert_eq!(profile.display_name.as_deref(), Some("Alice"));
+        assert_eq!(profile.avatar_url.as_deref(), Some("https://cdn.example.com/a.jpg"));
+    }
+
+    #[test]
+    fn json_ld_profile_pages_describe_their_main_entity() {
+        let json_ld = r#"<script type="application/ld+json">
+            {
+              "@context": "https://schema.org",
+              "@type": "ProfilePage",
+              "dateCreated": "2019-04-01T00:00:00Z",
+              "mainEntity": {
+                "@type": "Person",
+                "name": "Alice Smith",
+                "description": "Maintainer",
+                "image": { "@type": "ImageObject", "url": "images/alice.jpg" },
+                "homeLocation": { "@type": "Place", "name": "Lyon" },
+                "sameAs": ["https://github.com/alice", "/elsewhere/alice"]
+              }
+            }
+        </script>"#;
+        let html = page(&format!(r#"{}<meta property="og:title" content="Alice on Example">"#, json_ld), "");
+        let profile = extract(&html, PAGE_URL, &ProfileSelectors::default());
+        assert_eq!(profile.display_name.as_deref(), Some("Alice Smith"), "JSON-LD comes before Open Graph");
+        assert_eq!(profile.bio.as_deref(), Some("Maintainer"));
+        assert_eq!(profile.avatar_url.as_deref(), Some("https://example.com/users/images/alice.jpg"));
+        assert_eq!(profile.location.as_deref(), Some("Lyon"));
+        assert_eq!(profile.joined.as_deref(), Some("2019-04-01T00:00:00Z"));
+        assert_eq!(profile.links, ["https://github.com/alice", "https://example.com/elsewhere/alice"]);
+    }
+
+    #[test]
+    fn json_ld_people_are_found_in_graphs() {
+        let html = page(
+            r#"<script type="application/ld+json">
+                { "@graph": [
+                    { "@type": "WebSite", "name": "Example" },
+                    { "@type": ["Person"], "alternateName": "alice", "address": { "addressLocality": "Lyon", "addressCountry": "FR" } }
+                ] }
+            </script>
+            <script type="application/ld+json">not json</script>"#,
+            "",
+        );
+        let profile = extract(&html, PAGE_URL, &ProfileSelectors::default());
+        assert_eq!(profile.display_name.as_deref(), Some("alice"), "the WebSite's name isn't the owner's");
+        assert_eq!(profile.location.as_deref(), Some("Lyon, FR"));
+    }
+
+    #[test]
+    fn rel_me_links_are_absolute_deduplicated_and_exclude_the_page() {
+        let html = page(
+            r#"<link rel="me" href="https://mastodon.social/@alice">"#,
+            r#"<a rel="me nofollow" href="/blog">blog</a>
+               <a rel="me" href="https://mastodon.social/@alice">again</a>
+               <a rel="me" href="https://example.com/users/alice/">this page</a>
+               <a rel="me" href="javascript:alert(1)">not a link</a>
+               <a href="https://unrelated.example/">not rel=me</a>"#,
+        );
+        let profile = extract(&html, PAGE_URL, &ProfileSelectors::default());
+        assert_eq!(profile.links, ["https://mastodon.social/@alice", "https://example.com/blog"]);
+    }
+
+    #[test]
+    fn selectors_override_json_ld_and_open_graph() {
+        let html = page(
+            r#"<meta property="og:title" content="Alice on Example">
+               <meta property="og:image" content="https://example.com/og.png">
+               <script type="application/ld+json">{ "@type": "Person", "name": "Alice (JSON-LD)", "description": "From JSON-LD" }</script>"#,
+            r#"<h1 class="name"> Alice
+                 Smith </h1>
+               <img class="avatar" src="/img/alice.png">
+               <span class="where">Lyon</span>
+               <time class="since" datetime="2019-04-01">April 2019</time>
+               <ul class="links"><li><a href="https://alice.dev/">site</a></li><li><a href="/alice/feed">feed</a></li><li><a href="https://alice.dev/">dup</a></li></ul>"#,
+        );
+        let selectors = ProfileSelectors {
+            display_name: Some("h1.name".to_string()),
+            avatar: Some("img.avatar".to_string()),
+            location: Some(".where".to_string()),
+            joined: Some("time.since".to_string()),
+            links: Some("ul.links a".to_string()),
+            // Matches nothing, so JSON-LD still fills it
+            bio: Some(".bio".to_string()),
+        };
+        let profile = extract(&html, PAGE_URL, &selectors);
+        assert_eq!(profile.display_name.as_deref(), Some("Alice Smith"));
+        assert_eq!(profile.avatar_url.as_deref(), Some("https://example.com/img/alice.png"));
+        assert_eq!(profile.location.as_deref(), Some("Lyon"));
+        assert_eq!(profile.joined.as_deref(), Some("2019-04-01"));
+        assert_eq!(profile.bio.as_deref(), Some("From JSON-LD"));
+        assert_eq!(profile.links, ["https://alice.dev/", "https://example.com/alice/feed"]);
+    }
+
+    #[test]
+    fn pages_without_metadata_give_an_empty_profile() {
+        let profile = extract(&page("<title>Alice</title>", "<p>hello</p>"), PAGE_URL, &ProfileSelectors::default());
+        assert!(profile.is_empty(), "{:?}", profile);
+    }
+}
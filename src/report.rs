@@ -0,0 +1,321 @@
+//! Self-contained HTML investigation report (`--report`)
+
+use crate::avatar::AvatarCluster;
+use crate::checker::{CheckResult, SiteResult};
+use crate::pivot::Discovery;
+use std::fmt::Write;
+use std::time::{Duration, SystemTime};
+
+/// Scan metadata shown at the top of the report
+pub struct ReportMeta<'a> {
+    pub usernames: &'a [String],
+    pub started: SystemTime,
+    pub duration: Duration,
+    pub site_count: usize,
+    /// Links followed with `--recurse`
+    pub discoveries: &'a [Discovery],
+    /// Accounts grouped by matching avatars (`--avatars`)
+    pub avatar_clusters: &'a [AvatarCluster],
+}
+
+/// What each inconclusive outcome means and what to do about it, keyed by status tag
+const EXPLANATIONS: [(&str, &str); 10] = [
+    ("rate_limited", "The site throttled our requests (HTTP 429). Re-run later, lower --concurrency or raise --host-interval."),
+    ("challenge", "An anti-bot page (Cloudflare, captcha, LinkedIn's HTTP 999) was served instead of the profile. Check these sites by hand in a browser."),
+    ("blocked", "The site refuses to answer without logging in, so the account could be neither confirmed nor ruled out."),
+    ("dns_error", "The site's hostname could not be resolved. The platform may be gone, or DNS is filtered on this network."),
+    ("tls_error", "The secure connection failed (certificate or handshake problem). A proxy or middlebox may be interfering."),
+    ("invalid_username", "The username cannot exist on this site: it breaks the site's handle rules, or the site rejected it."),
+    ("unsupported", "The site cannot be searched by username (it uses numeric IDs, or has shut down)."),
+    ("timeout", "The site did not answer in time. Re-run later or through a different network."),
+    ("unavailable", "The connection failed or the site answered with a server error (5xx). Re-run later."),
+    ("error", "Any other failure, such as an unexpected HTTP status or an answer the site's rules don't recognise; the message gives the details."),
+];
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 2em auto; max-width: 70em; padding: 0 1em; color: #222; }
+h1 { margin-bottom: 0.2em; }
+h2 { border-bottom: 2px solid #ddd; padding-bottom: 0.2em; margin-top: 2em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; padding: 0.3em 0.6em; border-bottom: 1px solid #eee; vertical-align: top; }
+th { background: #f5f5f5; }
+.meta th { width: 10em; background: none; }
+.found { color: #1a7f37; font-weight: bold; }
+.not_found { color: #9a6700; }
+.inconclusive { color: #cf222e; }
+.category { margin: 1em 0 0.3em; color: #0969da; }
+.detail { color: #666; font-size: 0.9em; }
+#search { width: 100%; padding: 0.5em; font-size: 1em; margin-bottom: 0.5em; box-sizing: border-box; }
+a { color: #0969da; }
+"#;
+
+const SCRIPT: &str = r#"
+document.getElementById('search').addEventListener('input', function () {
+  var needle = this.value.toLowerCase();
+  document.querySelectorAll('#results tbody tr').forEach(function (row) {
+    row.style.display = row.textContent.toLowerCase().indexOf(needle) === -1 ? 'none' : '';
+  });
+});
+"#;
+
+/// Render the results, in the order given, as a single HTML page with inline styles and script
+/// and no external assets
+pub fn render_html(meta: &ReportMeta, results: &[&SiteResult]) -> String {
+    let found: Vec<&SiteResult> = results.iter().copied().filter(|r| matches!(r.result, CheckResult::Found)).collect();
+    let inconclusive = results.iter().filter(|r| !r.result.is_conclusive()).count();
+
+    let title = format!("Vidocq report: {}", meta.usernames.join(", "));
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        escape(&title),
+        STYLE,
+        escape(&title)
+    );
+
+    // Scan metadata
+    html.push_str("<table class=\"meta\">\n");
+    meta_row(&mut html, if meta.usernames.len() == 1 { "Username" } else { "Usernames" }, &meta.usernames.join(", "));
+    meta_row(&mut html, "Scanned", &httpdate::fmt_http_date(meta.started));
+    meta_row(&mut html, "Duration", &format!("{:.2} seconds", meta.duration.as_secs_f64()));
+    meta_row(&mut html, "Sites checked", &meta.site_count.to_string());
+    meta_row(&mut html, "Results", &format!("{} found, {} not found, {} inconclusive", found.len(), results.len() - found.len() - inconclusive, inconclusive));
+    meta_row(&mut html, "Tool", &format!("vidocq {}", env!("CARGO_PKG_VERSION")));
+    html.push_str("</table>\n");
+
+    // Found accounts, grouped by category
+    let _ = writeln!(html, "<h2>Found accounts ({})</h2>", found.len());
+    if found.is_empty() {
+        html.push_str("<p>No accounts found.</p>\n");
+    }
+    let mut by_category = found.clone();
+    by_category.sort_by(|a, b| a.category.cmp(&b.category).then(a.site.cmp(&b.site)).then(a.username.cmp(&b.username)));
+    let mut current_category: Option<&str> = None;
+    for result in &by_category {
+        if current_category != Some(result.category.as_str()) {
+            if current_category.is_some() {
+                html.push_str("</ul>\n");
+            }
+            current_category = Some(&result.category);
+            let _ = writeln!(html, "<h3 class=\"category\">{}</h3>\n<ul>", escape(&result.category));
+        }
+        let _ = writeln!(
+            html,
+            "<li><strong>{}</strong>{}: {} <span class=\"detail\">({:.0}% confidence)</span>{}</li>",
+            escape(&result.site),
+            if meta.usernames.len() > 1 { format!(" ({})", escape(&result.username)) } else { String::new() },
+            link(&result.url),
+            result.confidence * 100.0,
+            profile_summary(result)
+        );
+    }
+    if current_category.is_some() {
+        html.push_str("</ul>\n");
+    }
+
+    // Which account led to which
+    if !meta.discoveries.is_empty() {
+        let _ = writeln!(html, "<h2>Linked accounts ({})</h2>", meta.discoveries.len());
+        html.push_str("<table>\n<thead><tr><th>From</th><th>To</th><th>Link</th><th>Depth</th></tr></thead>\n<tbody>\n");
+        for discovery in meta.discoveries {
+            let _ = writeln!(
+                html,
+                "<tr><td>{} on {}</td><td>{} on {}</td><td>{}</td><td>{}</td></tr>",
+                escape(&discovery.from_username),
+                escape(&discovery.from_site),
+                escape(&discovery.username),
+                escape(&discovery.site),
+                link(&discovery.url),
+                discovery.depth
+            );
+        }
+        html.push_str("</tbody>\n</table>\n");
+    }
+
+    // Accounts sharing a profile picture
+    if !meta.avatar_clusters.is_empty() {
+        let _ = writeln!(html, "<h2>Likely same owner ({})</h2>", meta.avatar_clusters.len());
+        html.push_str("<p>These accounts use matching avatars.</p>\n");
+        for cluster in meta.avatar_clusters {
+            let closeness = if cluster.max_distance == 0 { "identical".to_string() } else { format!("within {} bits", cluster.max_distance) };
+            let _ = writeln!(html, "<h3>{} accounts, {}</h3>\n<ul>", cluster.members.len(), closeness);
+            for member in &cluster.members {
+                let _ = writeln!(
+                    html,
+                    "<li><strong>{}</strong> ({}): {} <span class=\"detail\">avatar {}</span></li>",
+                    escape(&member.site),
+                    escape(&member.username),
+                    link(&member.url),
+                    link(&member.avatar_url)
+                );
+            }
+            html.push_str("</ul>\n");
+        }
+    }
+
+    // Every result, filterable
+    let _ = writeln!(html, "<h2>All results ({})</h2>", results.len());
+    html.push_str("<input id=\"search\" type=\"search\" placeholder=\"Filter by site, category, status...\">\n");
+    html.push_str("<table id=\"results\">\n<thead><tr><th>Username</th><th>Site</th><th>Category</th><th>Result</th><th>Profile</th><th>Details</th></tr></thead>\n<tbody>\n");
+    for result in results {
+        let class = match result.result {
+            CheckResult::Found => "found",
+            CheckResult::NotFound => "not_found",
+            _ => "inconclusive",
+        };
+        let detail = if result.result.is_conclusive() { String::new() } else { result.result.to_string() };
+        let _ = writeln!(
+            html,
+            "<tr><td>{}{}</td><td>{}</td><td>{}</td><td class=\"{}\">{}</td><td>{}</td><td class=\"detail\">{}</td></tr>",
+            escape(&result.username),
+            result.seed.as_deref().map(|seed| format!(" <span class=\"detail\">(variant of {})</span>", escape(seed))).unwrap_or_default(),
+            escape(&result.site),
+            escape(&result.category),
+            class,
+            escape(result.result.label()),
+            link(&result.url),
+            escape(&detail)
+        );
+    }
+    html.push_str("</tbody>\n</table>\n");
+
+    // Explanations for the kinds of error that actually occurred
+    let present: Vec<(&str, &str)> = EXPLANATIONS
+        .iter()
+        .copied()
+        .filter(|(tag, _)| results.iter().any(|r| r.result.tag() == *tag))
+        .collect();
+    if !present.is_empty() {
+        html.push_str("<h2>About inconclusive results</h2>\n<table>\n");
+        for (tag, explanation) in present {
+            let label = results.iter().find(|r| r.result.tag() == tag).map(|r| r.result.label()).unwrap_or(tag);
+            let count = results.iter().filter(|r| r.result.tag() == tag).count();
+            let _ = writeln!(html, "<tr><th>{} ({})</th><td>{}</td></tr>", escape(label), count, escape(explanation));
+        }
+        html.push_str("</table>\n");
+    }
+
+    let _ = write!(html, "<script>{}</script>\n</body>\n</html>\n", SCRIPT);
+    html
+}
+
+/// Extracted profile fields (`--extract`) as a line under the account
+fn profile_summary(result: &SiteResult) -> String {
+    let Some(profile) = &result.profile else {
+        return String::new();
+    };
+    let mut parts: Vec<String> = [&profile.display_name, &profile.location, &profile.joined, &profile.bio]
+        .into_iter()
+        .flatten()
+        .map(|value| escape(value))
+        .collect();
+    parts.extend(profile.links.iter().map(|url| link(url)));
+    if parts.is_empty() {
+        return String::new();
+    }
+    format!("<br><span class=\"detail\">{}</span>", parts.join(" &middot; "))
+}
+
+fn meta_row(html: &mut String, name: &str, value: &str) {
+    let _ = writeln!(html, "<tr><th>{}</th><td>{}</td></tr>", escape(name), escape(value));
+}
+
+/// Clickable link for http(s) URLs only, so a hostile catalogue can't smuggle in `javascript:`
+fn link(url: &str) -> String {
+    if url.starts_with("https://") || url.starts_with("http://") {
+        format!("<a href=\"{0}\" target=\"_blank\" rel=\"noopener noreferrer\">{0}</a>", escape(url))
+    } else {
+        escape(url)
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::Profile;
+
+    fn found(url: &str, profile: Profile) -> SiteResult {
+        SiteResult {
+            username: "alice".to_string(),
+            seed: None,
+            site: "Example".to_string(),
+            url: url.to_string(),
+            category: "Social".to_string(),
+            result: CheckResult::Found,
+            status_code: Some(200),
+            final_url: None,
+            attempts: 1,
+            confidence: 0.9,
+            evidence: Vec::new(),
+            profile: Some(profile).filter(|p| !p.is_empty()),
+        }
+    }
+
+    fn render(results: &[SiteResult]) -> String {
+        let meta = ReportMeta {
+            usernames: &["alice".to_string()],
+            started: SystemTime::UNIX_EPOCH,
+            duration: Duration::from_secs(1),
+            site_count: results.len(),
+            discoveries: &[],
+            avatar_clusters: &[],
+        };
+        render_html(&meta, &results.iter().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn escape_covers_markup_and_quotes() {
+        assert_eq!(escape(r#"<a href="x" onclick='y'>&</a>"#), "&lt;a href=&quot;x&quot; onclick=&#39;y&#39;&gt;&amp;&lt;/a&gt;");
+        assert_eq!(escape("plain text"), "plain text");
+    }
+
+    #[test]
+    fn only_http_urls_become_links() {
+        assert_eq!(
+            link("https://example.com/a?b=1&c=\"2\""),
+            "<a href=\"https://example.com/a?b=1&amp;c=&quot;2&quot;\" target=\"_blank\" rel=\"noopener noreferrer\">https://example.com/a?b=1&amp;c=&quot;2&quot;</a>"
+        );
+        assert!(link("http://example.com/").starts_with("<a href="));
+        for hostile in ["javascript:alert(1)", "JavaScript:alert(1)", "data:text/html,<script>alert(1)</script>", " javascript:alert(1)"] {
+            let rendered = link(hostile);
+            assert!(!rendered.contains("<a"), "{}", rendered);
+            assert!(!rendered.contains("<script"), "{}", rendered);
+        }
+    }
+
+    #[test]
+    fn hostile_profiles_are_escaped() {
+        let profile = Profile {
+            display_name: Some("<script>alert('name')</script>".to_string()),
+            bio: Some("\"><img src=x onerror=alert(1)>".to_string()),
+            links: vec!["javascript:alert(document.cookie)".to_string(), "https://example.org/alice".to_string()],
+            ..Profile::default()
+        };
+        let html = render(&[found("javascript:alert('url')", profile)]);
+
+        assert!(!html.contains("<script>alert"), "{}", html);
+        assert!(!html.contains("<img"), "{}", html);
+        assert!(html.contains("&lt;script&gt;alert(&#39;name&#39;)&lt;/script&gt;"));
+        assert!(html.contains("&quot;&gt;&lt;img src=x onerror=alert(1)&gt;"));
+        assert!(!html.contains("href=\"javascript:"), "{}", html);
+        assert!(html.contains("<a href=\"https://example.org/alice\""));
+        // The only script is the report's own filter
+        assert_eq!(html.matches("<script>").count(), 1);
+    }
+}
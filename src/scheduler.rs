@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+/// Hosts tracked before idle ones are swept out of the map
+const SWEEP_AT: usize = 64;
+
+/// Politeness limits for one host
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HostLimits {
+    /// Requests allowed in flight at once
+    pub concurrency: usize,
+    /// Minimum time between the starts of two requests
+    pub interval: Duration,
+}
+
+impl HostLimits {
+    /// The stricter of two limits: the lower concurrency and the longer interval
+    fn merge(self, other: HostLimits) -> HostLimits {
+        HostLimits {
+            concurrency: self.concurrency.min(other.concurrency).max(1),
+            interval: self.interval.max(other.interval),
+        }
+    }
+}
+
+struct HostSlot {
+    state: Mutex<SlotState>,
+    /// Woken whenever a request to this host finishes
+    released: Notify,
+}
+
+struct SlotState {
+    limits: HostLimits,
+    in_flight: usize,
+    /// Earliest moment the next request to this host may start
+    next_start: Instant,
+}
+
+#[derive(Default)]
+struct Hosts {
+    slots: HashMap<String, Arc<HostSlot>>,
+    /// Size of `slots` at which idle hosts are next swept out
+    sweep_at: usize,
+}
+
+/// Per-host request scheduler, layered under the global concurrency limit.
+/// Every request to a host names its limits and the strictest seen so far apply to all of
+/// them, so sites sharing a host (e.g. several fandom.com entries) share one budget, and a
+/// redirect hop using the defaults can't loosen a site's own limits. Hosts with nothing in
+/// flight or pending are forgotten from time to time, so a long-running server doesn't keep
+/// every host it ever saw.
+#[derive(Default)]
+pub struct HostScheduler {
+    hosts: Mutex<Hosts>,
+}
+
+/// Held for the duration of a request; dropping it frees the host slot
+pub struct HostPermit {
+    slot: Arc<HostSlot>,
+}
+
+impl Drop for HostPermit {
+    fn drop(&mut self) {
+        self.slot.state.lock().unwrap().in_flight -= 1;
+        self.slot.released.notify_waiters();
+    }
+}
+
+impl HostScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait until a request to `url`'s host is allowed to start
+    pub async fn acquire(&self, url: &str, limits: HostLimits) -> HostPermit {
+        let slot = self.slot(&host_of(url));
+
+        // Take a place in flight, then reserve the next start time, and sleep without the lock
+        let start_at = loop {
+            // Registered before checking, so a release in between still wakes us
+            let released = slot.released.notified();
+            {
+                let mut state = slot.state.lock().unwrap();
+                state.limits = state.limits.merge(limits);
+                if state.in_flight < state.limits.concurrency {
+                    state.in_flight += 1;
+                    let start_at = state.next_start.max(Instant::now());
+                    state.next_start = start_at + state.limits.interval;
+                    break start_at;
+                }
+            }
+            released.await;
+        };
+        let permit = HostPermit { slot };
+        tokio::time::sleep_until(start_at).await;
+        permit
+    }
+
+    fn slot(&self, host: &str) -> Arc<HostSlot> {
+        let mut hosts = self.hosts.lock().unwrap();
+        if !hosts.slots.contains_key(host) && hosts.slots.len() >= hosts.sweep_at.max(SWEEP_AT) {
+            let now = Instant::now();
+            // Only the map holds an idle slot: no permit out, nobody waiting for one
+            hosts.slots.retain(|_, slot| Arc::strong_count(slot) > 1 || slot.state.lock().unwrap().next_start > now);
+            hosts.sweep_at = hosts.slots.len() * 2;
+        }
+        let slot = hosts.slots.entry(host.to_string()).or_insert_with(|| {
+            Arc::new(HostSlot {
+                state: Mutex::new(SlotState {
+                    // Loosest possible, so the first request's limits take over
+                    limits: HostLimits { concurrency: usize::MAX, interval: Duration::ZERO },
+                    in_flight: 0,
+                    next_start: Instant::now(),
+                }),
+                released: Notify::new(),
+            })
+        });
+        Arc::clone(slot)
+    }
+}
+
+/// Lowercased host of a URL, without any leading "www."; the key requests are scheduled by
+pub fn host_of(url: &str) -> String {
+    let host = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_lowercase()))
+        .unwrap_or_else(|| url.to_lowercase());
+    host.strip_prefix("www.").map(str::to_string).unwrap_or(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn limits(concurrency: usize, interval_ms: u64) -> HostLimits {
+        HostLimits { concurrency, interval: Duration::from_millis(interval_ms) }
+    }
+
+    /// Run `count` requests of `hold` each to one host at once; returns the most ever in flight
+    /// and each request's start, relative to the first
+    async fn run(scheduler: Arc<HostScheduler>, count: usize, limits: HostLimits, hold: Duration) -> (usize, Vec<Duration>) {
+        let started = Instant::now();
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let tasks: Vec<_> = (0..count)
+            .map(|_| {
+                let (scheduler, in_flight, peak) = (Arc::clone(&scheduler), Arc::clone(&in_flight), Arc::clone(&peak));
+                tokio::spawn(async move {
+                    let _permit = scheduler.acquire("https://example.com/alice", limits).await;
+                    let start = started.elapsed();
+                    peak.fetch_max(in_flight.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                    tokio::time::sleep(hold).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    start
+                })
+            })
+            .collect();
+        let mut starts = Vec::new();
+        for task in tasks {
+            starts.push(task.await.unwrap());
+        }
+        starts.sort();
+        (peak.load(Ordering::SeqCst), starts)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn concurrency_is_capped() {
+        let (peak, starts) = run(Arc::new(HostScheduler::new()), 5, limits(2, 0), Duration::from_secs(1)).await;
+        assert_eq!(peak, 2);
+        assert_eq!(starts, [0, 0, 1, 1, 2].map(Duration::from_secs));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn starts_are_spaced_by_the_interval() {
+        let (_, starts) = run(Arc::new(HostScheduler::new()), 4, limits(10, 250), Duration::ZERO).await;
+        assert_eq!(starts, [0, 250, 500, 750].map(Duration::from_millis));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn the_strictest_limits_win() {
+        let scheduler = Arc::new(HostScheduler::new());
+        // A redirect hop with the defaults reaches the host first...
+        drop(scheduler.acquire("https://www.example.com/", limits(4, 0)).await);
+        // ...then the site with its own, stricter limits
+        let (peak, starts) = run(Arc::clone(&scheduler), 3, limits(1, 500), Duration::ZERO).await;
+        assert_eq!(peak, 1);
+        assert_eq!(starts, [0, 500, 1000].map(Duration::from_millis));
+
+        // Looser limits later don't undo them
+        let (peak, _) = run(scheduler, 3, limits(4, 0), Duration::from_secs(1)).await;
+        assert_eq!(peak, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn idle_hosts_are_forgotten() {
+        let scheduler = HostScheduler::new();
+        let busy = scheduler.acquire("https://busy.example/", limits(1, 0)).await;
+        // Up to the sweep threshold, with a start still pending on each for now
+        for i in 1..SWEEP_AT {
+            drop(scheduler.acquire(&format!("https://host{}.example/", i), limits(1, 100)).await);
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        drop(scheduler.acquire("https://new.example/", limits(1, 0)).await);
+
+        let hosts = scheduler.hosts.lock().unwrap();
+        let mut kept: Vec<&str> = hosts.slots.keys().map(String::as_str).collect();
+        kept.sort();
+        assert_eq!(kept, ["busy.example", "new.example"]);
+        drop(busy);
+    }
+}
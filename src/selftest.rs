@@ -0,0 +1,184 @@
+//! Catalogue health checks (`vidocq selftest`)
+//!
+//! Sites change their markup, status codes and bot defences, and a catalogue entry that worked
+//! last month can quietly start reporting every username as taken, or none. Each site can name a
+//! `claimed` username known to exist and an `unclaimed` one known not to; checking both shows
+//! whether the site's detection still tells them apart.
+
+use crate::checker::{CheckResult, SiteResult};
+use crate::sites::Site;
+use serde::Serialize;
+
+/// Unclaimed sample for sites that don't name one: long and random enough to be free everywhere,
+/// and short and plain enough for every catalogue `username_pattern`
+pub const DEFAULT_UNCLAIMED: &str = "zq7vxk2dw9jp3m";
+
+/// The usernames to check for a site and the result each should produce
+pub fn samples(site: &Site) -> Vec<(String, CheckResult)> {
+    let unclaimed = site.unclaimed.as_deref().unwrap_or(DEFAULT_UNCLAIMED);
+    site.claimed
+        .iter()
+        .map(|claimed| (claimed.clone(), CheckResult::Found))
+        .chain(std::iter::once((unclaimed.to_string(), CheckResult::NotFound)))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Health {
+    /// A sample came back conclusively wrong: the site's detection needs fixing
+    Broken,
+    /// No sample came back wrong, but at least one couldn't be checked (rate limit, challenge, error)
+    Inconclusive,
+    /// Every sample produced the expected result
+    Healthy,
+}
+
+/// One sample username and what checking it produced
+#[derive(Debug, Clone, Serialize)]
+pub struct SampleCheck {
+    pub username: String,
+    /// Status tag the check should produce: `found` or `not_found`
+    pub expect: String,
+    pub actual: String,
+    pub passed: bool,
+    /// The checker's evidence, for failed and inconclusive checks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SiteHealth {
+    pub site: String,
+    pub category: String,
+    pub health: Health,
+    /// False when the site names no `claimed` sample, so only false positives can be detected
+    pub has_claimed: bool,
+    pub checks: Vec<SampleCheck>,
+}
+
+/// Judge a site from the results of checking its [`samples`]
+pub fn assess(site: &Site, results: Vec<(CheckResult, SiteResult)>) -> SiteHealth {
+    let checks: Vec<SampleCheck> = results
+        .into_iter()
+        .map(|(expect, result)| {
+            let passed = result.result == expect;
+            let detail = result.evidence.iter().map(|e| e.detail.as_str()).collect::<Vec<_>>().join("; ");
+            SampleCheck {
+                username: result.username,
+                expect: expect.tag().to_string(),
+                actual: result.result.tag().to_string(),
+                passed,
+                detail: Some(detail).filter(|d| !passed && !d.is_empty()),
+            }
+        })
+        .collect();
+
+    // An invalid_username verdict is as wrong as the opposite answer: the sample never reaches the site
+    let wrong = |check: &SampleCheck| !check.passed && matches!(check.actual.as_str(), "found" | "not_found" | "invalid_username");
+    let health = if checks.iter().any(wrong) {
+        Health::Broken
+    } else if checks.iter().any(|check| !check.passed) {
+        Health::Inconclusive
+    } else {
+        Health::Healthy
+    };
+    SiteHealth {
+        site: site.name.clone(),
+        category: site.category.clone(),
+        health,
+        has_claimed: site.claimed.is_some(),
+        checks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::{Evidence, EvidenceSource};
+    use crate::sites::{parse_manifest, ManifestFormat};
+
+    fn site(samples: &str) -> Site {
+        let manifest = format!("[[site]]\nname = \"Example\"\nurl = \"https://example.com/{{}}\"\ncategory = \"Test\"\n{}", samples);
+        parse_manifest(&manifest, ManifestFormat::Toml).unwrap().remove(0)
+    }
+
+    fn checked(username: &str, result: CheckResult, detail: &str) -> SiteResult {
+        SiteResult {
+            username: username.to_string(),
+            seed: None,
+            site: "Example".to_string(),
+            url: format!("https://example.com/{}", username),
+            category: "Test".to_string(),
+            result,
+            status_code: Some(200),
+            final_url: None,
+            attempts: 1,
+            confidence: 0.9,
+            evidence: vec![Evidence::found(EvidenceSource::Status, 0.5, detail)],
+            profile: None,
+        }
+    }
+
+    #[test]
+    fn samples_are_the_claimed_then_the_unclaimed_username() {
+        assert_eq!(
+            samples(&site("claimed = \"alice\"\nunclaimed = \"nobody42\"\n")),
+            [("alice".to_string(), CheckResult::Found), ("nobody42".to_string(), CheckResult::NotFound)]
+        );
+        assert_eq!(samples(&site("")), [(DEFAULT_UNCLAIMED.to_string(), CheckResult::NotFound)]);
+    }
+
+    #[test]
+    fn expected_results_are_healthy() {
+        let site = site("claimed = \"alice\"\n");
+        let health = assess(&site, vec![
+            (CheckResult::Found, checked("alice", CheckResult::Found, "HTTP 200")),
+            (CheckResult::NotFound, checked(DEFAULT_UNCLAIMED, CheckResult::NotFound, "HTTP 404")),
+        ]);
+        assert_eq!(health.health, Health::Healthy);
+        assert!(health.has_claimed);
+        assert_eq!((health.site.as_str(), health.category.as_str()), ("Example", "Test"));
+        let checks: Vec<(&str, &str, &str, bool)> =
+            health.checks.iter().map(|c| (c.username.as_str(), c.expect.as_str(), c.actual.as_str(), c.passed)).collect();
+        assert_eq!(checks, [("alice", "found", "found", true), (DEFAULT_UNCLAIMED, "not_found", "not_found", true)]);
+        // Evidence is only kept for checks that need looking into
+        assert!(health.checks.iter().all(|c| c.detail.is_none()));
+    }
+
+    #[test]
+    fn wrong_answers_are_broken() {
+        let site = site("claimed = \"alice\"\n");
+        for (claimed, unclaimed) in [
+            // Everyone exists
+            (CheckResult::Found, CheckResult::Found),
+            // Nobody does
+            (CheckResult::NotFound, CheckResult::NotFound),
+            // The sample never reaches the site, which is no better
+            (CheckResult::Found, CheckResult::InvalidUsername("too long".to_string())),
+        ] {
+            let health = assess(&site, vec![
+                (CheckResult::Found, checked("alice", claimed.clone(), "claimed evidence")),
+                (CheckResult::NotFound, checked("nobody", unclaimed.clone(), "unclaimed evidence")),
+            ]);
+            assert_eq!(health.health, Health::Broken, "{:?} / {:?}", claimed, unclaimed);
+            let failed = health.checks.iter().find(|c| !c.passed).unwrap();
+            assert!(failed.detail.as_deref().is_some_and(|d| d.ends_with("evidence")), "{:?}", failed);
+        }
+    }
+
+    #[test]
+    fn unanswered_checks_are_inconclusive_unless_another_is_wrong() {
+        let health = assess(&site(""), vec![(CheckResult::NotFound, checked(DEFAULT_UNCLAIMED, CheckResult::Challenge("captcha".to_string()), "captcha"))]);
+        assert_eq!(health.health, Health::Inconclusive);
+        assert!(!health.has_claimed);
+        assert_eq!(health.checks[0].detail.as_deref(), Some("captcha"));
+
+        let health = assess(&site("claimed = \"alice\"\n"), vec![
+            (CheckResult::Found, checked("alice", CheckResult::RateLimited { retry_after_secs: None }, "HTTP 429")),
+            (CheckResult::NotFound, checked(DEFAULT_UNCLAIMED, CheckResult::Found, "HTTP 200")),
+        ]);
+        assert_eq!(health.health, Health::Broken);
+        assert!(Health::Broken < Health::Inconclusive && Health::Inconclusive < Health::Healthy, "worst sorts first");
+    }
+}
@@ -0,0 +1,250 @@
+//! Local REST API (`vidocq serve`)
+//!
+//! - `POST /scans` with `{"username": "...", "sites": ["GitHub", ...]}` starts a scan
+//!   (`sites` is optional and defaults to the whole catalogue) and answers `202` with its id
+//! - `GET /scans/{id}` reports progress and the results so far
+//! - `GET /scans/{id}/events` streams results as Server-Sent Events: one `result` event per
+//!   site, replayed from the start for late subscribers, then a final `done` event
+//! - `GET /sites` lists the catalogue
+//!
+//! Scans share one `AccountChecker`, so the per-host limits and proxies apply across all of them,
+//! and `concurrency` bounds the sites checked at once across all scans, not per scan.
+//! The last [`KEPT_SCANS`] finished scans are kept in memory; older ones answer `404`.
+
+use crate::checker::{AccountChecker, CheckResult, SiteResult};
+use crate::sites::Site;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Json, Response};
+use axum::routing::{get, post};
+use axum::Router;
+use futures::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{watch, Semaphore};
+
+/// How many finished scans to keep for `GET /scans/{id}`; running scans are never dropped
+pub const KEPT_SCANS: usize = 100;
+
+#[derive(Clone)]
+struct AppState {
+    checker: Arc<AccountChecker>,
+    sites: Arc<Vec<Site>>,
+    concurrency: usize,
+    /// Checks in flight across every scan
+    permits: Arc<Semaphore>,
+    scans: Arc<Mutex<HashMap<u64, Arc<Scan>>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+struct Scan {
+    username: String,
+    total: usize,
+    results: Mutex<Vec<SiteResult>>,
+    finished: AtomicBool,
+    /// Bumped after every new result and once more when the scan finishes
+    updates: watch::Sender<usize>,
+}
+
+#[derive(Deserialize)]
+struct ScanRequest {
+    username: String,
+    /// Site names to check; the whole catalogue when absent
+    #[serde(default)]
+    sites: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+struct ScanStarted {
+    id: u64,
+    status_url: String,
+    events_url: String,
+}
+
+#[derive(Serialize)]
+struct ScanStatus {
+    id: u64,
+    username: String,
+    /// "running" or "finished"
+    state: &'static str,
+    total: usize,
+    completed: usize,
+    found: usize,
+    results: Vec<SiteResult>,
+}
+
+/// Build the API routes around a shared checker and catalogue
+pub fn router(checker: Arc<AccountChecker>, sites: Vec<Site>, concurrency: usize) -> Router {
+    let state = AppState {
+        checker,
+        sites: Arc::new(sites),
+        concurrency,
+        permits: Arc::new(Semaphore::new(concurrency.max(1))),
+        scans: Arc::new(Mutex::new(HashMap::new())),
+        next_id: Arc::new(AtomicU64::new(1)),
+    };
+    Router::new()
+        .route("/scans", post(start_scan))
+        .route("/scans/:id", get(scan_status))
+        .route("/scans/:id/events", get(scan_events))
+        .route("/sites", get(list_sites))
+        .with_state(state)
+}
+
+/// Serve the API on `bind` until the process is stopped
+pub async fn serve(bind: SocketAddr, checker: Arc<AccountChecker>, sites: Vec<Site>, concurrency: usize) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(bind).await?;
+    axum::serve(listener, router(checker, sites, concurrency)).await
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(serde_json::json!({ "error": message.into() }))).into_response()
+}
+
+async fn start_scan(State(state): State<AppState>, Json(request): Json<ScanRequest>) -> Response {
+    let username = request.username.trim().to_string();
+    if username.is_empty() {
+        return error(StatusCode::BAD_REQUEST, "username is empty");
+    }
+
+    let sites: Vec<Site> = match &request.sites {
+        None => state.sites.to_vec(),
+        Some(names) => {
+            let unknown: Vec<&str> = names
+                .iter()
+                .filter(|name| !state.sites.iter().any(|site| &site.name == *name))
+                .map(String::as_str)
+                .collect();
+            if !unknown.is_empty() {
+                return error(StatusCode::BAD_REQUEST, format!("unknown sites: {}", unknown.join(", ")));
+            }
+            state.sites.iter().filter(|site| names.contains(&site.name)).cloned().collect()
+        }
+    };
+
+    let id = state.next_id.fetch_add(1, Ordering::Relaxed);
+    let scan = Arc::new(Scan {
+        username: username.clone(),
+        total: sites.len(),
+        results: Mutex::new(Vec::new()),
+        finished: AtomicBool::new(false),
+        updates: watch::channel(0).0,
+    });
+    state.scans.lock().unwrap().insert(id, Arc::clone(&scan));
+
+    // Each check waits for a permit from the pool every scan shares, so a burst of scans can't
+    // multiply the number of requests in flight
+    let permits = Arc::clone(&state.permits);
+    let mut results = Box::pin(
+        stream::iter(sites)
+            .map(move |site| {
+                let checker = Arc::clone(&state.checker);
+                let permits = Arc::clone(&permits);
+                let username = username.clone();
+                async move {
+                    let _permit = permits.acquire_owned().await.expect("the semaphore is never closed");
+                    checker.check_account(&site, &username).await
+                }
+            })
+            .buffer_unordered(state.concurrency.max(1)),
+    );
+    let scans = Arc::clone(&state.scans);
+    tokio::spawn(async move {
+        while let Some(result) = results.next().await {
+            scan.results.lock().unwrap().push(result);
+            scan.updates.send_modify(|count| *count += 1);
+        }
+        // Finish and evict under the lock, so a scan seen as finished has already made room
+        {
+            let mut scans = scans.lock().unwrap();
+            scan.finished.store(true, Ordering::SeqCst);
+            evict_finished(&mut scans);
+        }
+        scan.updates.send_modify(|_| {});
+    });
+
+    let started = ScanStarted {
+        id,
+        status_url: format!("/scans/{}", id),
+        events_url: format!("/scans/{}/events", id),
+    };
+    (StatusCode::ACCEPTED, Json(started)).into_response()
+}
+
+/// Drop the oldest finished scans beyond [`KEPT_SCANS`]. Subscribers to a dropped scan's events
+/// keep their own handle to it, so their streams still finish.
+fn evict_finished(scans: &mut HashMap<u64, Arc<Scan>>) {
+    let mut finished: Vec<u64> = scans.iter().filter(|(_, scan)| scan.finished.load(Ordering::SeqCst)).map(|(id, _)| *id).collect();
+    if finished.len() > KEPT_SCANS {
+        finished.sort_unstable();
+        for id in &finished[..finished.len() - KEPT_SCANS] {
+            scans.remove(id);
+        }
+    }
+}
+
+fn find_scan(state: &AppState, id: u64) -> Option<Arc<Scan>> {
+    state.scans.lock().unwrap().get(&id).cloned()
+}
+
+async fn scan_status(State(state): State<AppState>, Path(id): Path<u64>) -> Response {
+    let Some(scan) = find_scan(&state, id) else {
+        return error(StatusCode::NOT_FOUND, format!("no scan with id {}", id));
+    };
+    // Read the flag first so a "finished" status never misses the last results
+    let finished = scan.finished.load(Ordering::SeqCst);
+    let results = scan.results.lock().unwrap().clone();
+    Json(ScanStatus {
+        id,
+        username: scan.username.clone(),
+        state: if finished { "finished" } else { "running" },
+        total: scan.total,
+        completed: results.len(),
+        found: results.iter().filter(|r| r.result == CheckResult::Found).count(),
+        results,
+    })
+    .into_response()
+}
+
+async fn scan_events(State(state): State<AppState>, Path(id): Path<u64>) -> Response {
+    let Some(scan) = find_scan(&state, id) else {
+        return error(StatusCode::NOT_FOUND, format!("no scan with id {}", id));
+    };
+    Sse::new(result_events(scan)).keep_alive(KeepAlive::default()).into_response()
+}
+
+/// Every result of the scan in arrival order, then `done`, waiting for new results as needed
+fn result_events(scan: Arc<Scan>) -> impl Stream<Item = Result<Event, Infallible>> {
+    let updates = scan.updates.subscribe();
+    stream::unfold((scan, updates, Some(0)), |(scan, mut updates, next)| async move {
+        let next = next?;
+        loop {
+            let finished = scan.finished.load(Ordering::SeqCst);
+            let pending = scan.results.lock().unwrap().get(next).cloned();
+            if let Some(result) = pending {
+                let event = Event::default()
+                    .event("result")
+                    .json_data(&result)
+                    .unwrap_or_else(|e| Event::default().event("error").data(e.to_string()));
+                return Some((Ok(event), (scan, updates, Some(next + 1))));
+            }
+            if finished {
+                let event = Event::default().event("done").data(format!("{{\"completed\":{}}}", next));
+                return Some((Ok(event), (scan, updates, None)));
+            }
+            // The sender lives in the scan we hold, so this only fails if the scan task panicked
+            if updates.changed().await.is_err() {
+                return None;
+            }
+        }
+    })
+}
+
+async fn list_sites(State(state): State<AppState>) -> Json<Vec<Site>> {
+    Json(state.sites.to_vec())
+}
@@ -1,162 +1,669 @@
+use crate::selftest::DEFAULT_UNCLAIMED;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Default site catalogue, embedded at build time
+pub const DEFAULT_MANIFEST: &str = include_str!("../sites.toml");
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Site {
     pub name: String,
     pub url: String,
     pub category: String,
+    /// Regex the username must match for the site to accept it; usernames that don't are not requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username_pattern: Option<String>,
+    /// Why the site can't be searched by username; such sites are reported as unsupported
+    /// without sending a request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unsupported: Option<String>,
+    /// A username known to exist on the site, for `vidocq selftest`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claimed: Option<String>,
+    /// A username known not to exist on the site, for `vidocq selftest`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unclaimed: Option<String>,
+    /// How to send the check when a plain GET of `url` isn't enough, e.g. a POST to a JSON API
+    #[serde(default, skip_serializing_if = "RequestSpec::is_empty")]
+    pub request: RequestSpec,
+    #[serde(default, skip_serializing_if = "Rules::is_empty")]
+    pub rules: Rules,
+    /// Per-host politeness limits; unset fields use the checker's defaults
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+    /// CSS selectors for profile metadata (`--extract`), tried before JSON-LD and Open Graph tags
+    #[serde(default, skip_serializing_if = "ProfileSelectors::is_empty")]
+    pub profile: ProfileSelectors,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimit {
+    /// Requests to this site's host allowed in flight at once
+    pub concurrency: Option<usize>,
+    /// Minimum milliseconds between the starts of two requests to this site's host
+    pub interval_ms: Option<u64>,
+}
+
+/// The request a check sends. `{}` in `url`, header and cookie values and body strings is
+/// replaced by the username; `rules` are then applied to this request's response.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RequestSpec {
+    /// HTTP method, GET when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// URL to probe instead of the profile `url`, which stays the link shown in results
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Extra request headers; a `User-Agent` here replaces the default one
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub cookies: BTreeMap<String, String>,
+    /// JSON request body
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub json: Option<serde_json::Value>,
+    /// URL-encoded form request body
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub form: BTreeMap<String, String>,
+}
+
+impl RequestSpec {
+    pub fn is_empty(&self) -> bool {
+        self.method.is_none()
+            && self.url.is_none()
+            && self.headers.is_empty()
+            && self.cookies.is_empty()
+            && self.json.is_none()
+            && self.form.is_empty()
+    }
+
+    /// Whether the username goes anywhere in the request besides `url`
+    fn sends_username(&self) -> bool {
+        self.url.as_ref().is_some_and(|url| url.contains("{}"))
+            || self.headers.values().chain(self.cookies.values()).chain(self.form.values()).any(|v| v.contains("{}"))
+            || self.json.as_ref().is_some_and(json_mentions_username)
+    }
+}
+
+fn json_mentions_username(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::String(s) => s.contains("{}"),
+        serde_json::Value::Array(items) => items.iter().any(json_mentions_username),
+        serde_json::Value::Object(map) => map.values().any(json_mentions_username),
+        _ => false,
+    }
+}
+
+/// Per-site detection rules, evaluated by the checker before the generic heuristics.
+/// All string matching is case-insensitive; `{}` in `presence` is replaced by the username.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    /// Status codes that mean the account does not exist
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub error_codes: Vec<u16>,
+    /// Status codes that mean the account exists, whatever the body says
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub found_codes: Vec<u16>,
+    /// Status codes that mean the site refuses to answer without authentication
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub blocked_codes: Vec<u16>,
+    /// Status codes that mean the site rejected the username itself
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub invalid_codes: Vec<u16>,
+    /// Body strings that mean the account does not exist
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub absence: Vec<String>,
+    /// Body strings that prove the account exists
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub presence: Vec<String>,
+    /// Page locations where the username must appear for the account to exist
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub username_in: Vec<UsernameLocation>,
+    /// `<title>` substrings of the site's generic page: a title containing one of these but not
+    /// the username means the account does not exist
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub generic_title: Vec<String>,
+    /// Final URL or meta refresh substrings showing the site redirected away from the profile
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub redirect_away: Vec<String>,
+    /// A meta refresh to a URL without the username means the account does not exist
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub refresh_needs_username: bool,
+    /// Pages smaller than this (in bytes) are empty shells unless `presence`/`username_in` match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_body_size: Option<usize>,
+    /// A response matching neither `absence` nor `presence`/`username_in` is an error rather than
+    /// not found: for APIs whose every answer is known, so anything else means the API changed
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub require_marker: bool,
+}
+
+impl Rules {
+    pub fn is_empty(&self) -> bool {
+        self.error_codes.is_empty()
+            && self.found_codes.is_empty()
+            && self.blocked_codes.is_empty()
+            && self.invalid_codes.is_empty()
+            && self.absence.is_empty()
+            && self.presence.is_empty()
+            && self.username_in.is_empty()
+            && self.generic_title.is_empty()
+            && self.redirect_away.is_empty()
+            && !self.refresh_needs_username
+            && self.min_body_size.is_none()
+            && !self.require_marker
+    }
+
+    /// Whether the rules name any proof that the account exists
+    pub fn has_proof_markers(&self) -> bool {
+        !self.presence.is_empty() || !self.username_in.is_empty()
+    }
+}
+
+/// CSS selectors locating profile fields on a found page. A matching element yields its `content`
+/// attribute (for `<meta>` tags), else `src` for `avatar`, `href` for `links` and `datetime` for
+/// `joined`, else its text.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileSelectors {
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub avatar: Option<String>,
+    pub location: Option<String>,
+    /// Every match is collected, not just the first
+    pub links: Option<String>,
+    pub joined: Option<String>,
+}
+
+impl ProfileSelectors {
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// (field name, selector) for every field that is set
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("display_name", &self.display_name),
+            ("bio", &self.bio),
+            ("avatar", &self.avatar),
+            ("location", &self.location),
+            ("links", &self.links),
+            ("joined", &self.joined),
+        ]
+        .into_iter()
+        .filter_map(|(field, selector)| selector.as_deref().map(|s| (field, s)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsernameLocation {
+    /// The `<title>` tag
+    Title,
+    /// Anywhere in a page that has an `og:title` meta tag
+    OgTitle,
+    /// Visible page text, with scripts and styles stripped
+    Text,
+}
+
+impl UsernameLocation {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            UsernameLocation::Title => "the page title",
+            UsernameLocation::OgTitle => "a page with an og:title tag",
+            UsernameLocation::Text => "the visible page text",
+        }
+    }
+}
+
+/// On-disk shape of a catalogue: a list of `[[site]]` tables in TOML,
+/// or `{"site": [...]}` in JSON
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    #[serde(default)]
+    site: Vec<Site>,
+}
+
+#[derive(Serialize)]
+struct ManifestRef<'a> {
+    site: &'a [Site],
+}
+
+/// JSON catalogues may also be a bare array of sites
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum JsonManifest {
+    Table(Manifest),
+    List(Vec<Site>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestFormat {
+    Toml,
+    Json,
+}
+
+impl ManifestFormat {
+    /// Pick the format from the file extension, defaulting to TOML
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => ManifestFormat::Json,
+            _ => ManifestFormat::Toml,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ManifestError {
+    Io { path: PathBuf, source: std::io::Error },
+    Parse { format: ManifestFormat, message: String },
+    /// One entry per malformed site: (index in the catalogue, site name, problem)
+    Invalid(Vec<(usize, String, String)>),
+    Empty,
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestError::Io { path, source } => {
+                write!(f, "cannot read site catalogue {}: {}", path.display(), source)
+            }
+            ManifestError::Parse { format, message } => {
+                let kind = match format {
+                    ManifestFormat::Toml => "TOML",
+                    ManifestFormat::Json => "JSON",
+                };
+                write!(f, "malformed {} site catalogue: {}", kind, message.trim_end())
+            }
+            ManifestError::Invalid(problems) => {
+                write!(f, "site catalogue has {} invalid entr{}:", problems.len(), if problems.len() == 1 { "y" } else { "ies" })?;
+                for (index, name, problem) in problems {
+                    write!(f, "\n  site #{} ({}): {}", index + 1, name, problem)?;
+                }
+                Ok(())
+            }
+            ManifestError::Empty => write!(f, "site catalogue contains no sites"),
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ManifestError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Parse and validate a site catalogue from text
+pub fn parse_manifest(text: &str, format: ManifestFormat) -> Result<Vec<Site>, ManifestError> {
+    let sites = match format {
+        ManifestFormat::Toml => toml::from_str::<Manifest>(text)
+            .map(|manifest| manifest.site)
+            .map_err(|e| ManifestError::Parse { format, message: e.to_string() })?,
+        ManifestFormat::Json => match serde_json::from_str::<JsonManifest>(text) {
+            Ok(JsonManifest::Table(manifest)) => manifest.site,
+            Ok(JsonManifest::List(sites)) => sites,
+            Err(_) => {
+                // The untagged error is unhelpful - re-parse as a list to get line/column
+                let message = serde_json::from_str::<Vec<Site>>(text)
+                    .err()
+                    .map(|e| e.to_string())
+                    .unwrap_or_default();
+                return Err(ManifestError::Parse { format, message });
+            }
+        },
+    };
+
+    validate_sites(&sites)?;
+    Ok(sites)
+}
+
+/// Load a site catalogue from a TOML or JSON file
+pub fn load_sites(path: &Path) -> Result<Vec<Site>, ManifestError> {
+    let text = std::fs::read_to_string(path).map_err(|source| ManifestError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    parse_manifest(&text, ManifestFormat::from_path(path))
+}
+
+/// Write sites as a catalogue that `load_sites` reads back
+pub fn to_manifest(sites: &[Site], format: ManifestFormat) -> Result<String, String> {
+    let manifest = ManifestRef { site: sites };
+    match format {
+        ManifestFormat::Toml => toml::to_string(&manifest).map_err(|e| e.to_string()),
+        ManifestFormat::Json => serde_json::to_string_pretty(&manifest).map(|json| json + "\n").map_err(|e| e.to_string()),
+    }
+}
+
+/// Check every entry and report all problems at once rather than stopping at the first
+pub fn validate_sites(sites: &[Site]) -> Result<(), ManifestError> {
+    if sites.is_empty() {
+        return Err(ManifestError::Empty);
+    }
+
+    let mut problems = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (index, site) in sites.iter().enumerate() {
+        let name = if site.name.trim().is_empty() { "<unnamed>".to_string() } else { site.name.clone() };
+
+        if site.name.trim().is_empty() {
+            problems.push((index, name.clone(), "name is empty".to_string()));
+        } else if !seen.insert(site.name.as_str()) {
+            problems.push((index, name.clone(), "duplicate site name".to_string()));
+        }
+
+        if site.category.trim().is_empty() {
+            problems.push((index, name.clone(), "category is empty".to_string()));
+        }
+
+        if !site.url.starts_with("https://") && !site.url.starts_with("http://") {
+            problems.push((index, name.clone(), format!("url must start with http:// or https:// (got \"{}\")", site.url)));
+        }
+        if !site.url.contains("{}") {
+            problems.push((index, name.clone(), format!("url has no {{}} username placeholder (got \"{}\")", site.url)));
+        }
+        problems.extend(request_problems(&site.request).into_iter().map(|problem| (index, name.clone(), problem)));
+
+        let username_pattern = match &site.username_pattern {
+            Some(pattern) => match regex::Regex::new(pattern) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    problems.push((index, name.clone(), format!("username_pattern is not a valid regex: {}", e)));
+                    None
+                }
+            },
+            None => None,
+        };
+
+        if site.unsupported.as_ref().is_some_and(|reason| reason.trim().is_empty()) {
+            problems.push((index, name.clone(), "unsupported needs a reason".to_string()));
+        }
+
+        for (field, sample) in [("claimed", &site.claimed), ("unclaimed", &site.unclaimed)] {
+            let Some(sample) = sample else { continue };
+            if sample.trim().is_empty() {
+                problems.push((index, name.clone(), format!("{} is empty", field)));
+            } else if username_pattern.as_ref().is_some_and(|pattern| !pattern.is_match(sample)) {
+                problems.push((index, name.clone(), format!("{} does not match username_pattern (got \"{}\")", field, sample)));
+            }
+        }
+        // selftest falls back on the default, so it has to be a username the site could have
+        if site.unclaimed.is_none() && site.unsupported.is_none() && username_pattern.as_ref().is_some_and(|pattern| !pattern.is_match(DEFAULT_UNCLAIMED)) {
+            problems.push((index, name.clone(), format!("username_pattern rejects the default unclaimed sample \"{}\"; name an unclaimed one", DEFAULT_UNCLAIMED)));
+        }
+        if site.claimed.is_some() && site.claimed == site.unclaimed {
+            problems.push((index, name.clone(), "claimed and unclaimed are the same username".to_string()));
+        }
+
+        for (field, selector) in site.profile.iter() {
+            // scraper's parse errors are debug dumps, so quote the selector instead
+            if scraper::Selector::parse(selector).is_err() {
+                problems.push((index, name.clone(), format!("profile.{} is not a valid CSS selector (got \"{}\")", field, selector)));
+            }
+        }
+
+        let rules = &site.rules;
+        let code_lists = [
+            ("error_codes", &rules.error_codes),
+            ("found_codes", &rules.found_codes),
+            ("blocked_codes", &rules.blocked_codes),
+            ("invalid_codes", &rules.invalid_codes),
+        ];
+        for (i, (first, first_codes)) in code_lists.iter().enumerate() {
+            for (second, second_codes) in &code_lists[i + 1..] {
+                if let Some(code) = first_codes.iter().find(|code| second_codes.contains(code)) {
+                    problems.push((index, name.clone(), format!("status {} is in both {} and {}", code, first, second)));
+                }
+            }
+        }
+        if rules.require_marker && rules.absence.is_empty() && !rules.has_proof_markers() {
+            problems.push((index, name.clone(), "require_marker needs absence, presence or username_in markers".to_string()));
+        }
+        if site.rate_limit.is_some_and(|limit| limit.concurrency == Some(0)) {
+            problems.push((index, name.clone(), "rate_limit.concurrency must be at least 1".to_string()));
+        }
+        if site.rules.absence.iter().chain(&site.rules.presence).chain(&site.rules.generic_title).chain(&site.rules.redirect_away).any(|s| s.is_empty()) {
+            problems.push((index, name, "rules contain an empty match string".to_string()));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(ManifestError::Invalid(problems))
+    }
+}
+
+fn request_problems(request: &RequestSpec) -> Vec<String> {
+    let mut problems = Vec::new();
+    let method = request.method.as_deref().unwrap_or("GET");
+    if reqwest::Method::from_bytes(method.to_ascii_uppercase().as_bytes()).is_err() {
+        problems.push(format!("request.method is not an HTTP method (got \"{}\")", method));
+    }
+    let has_body = request.json.is_some() || !request.form.is_empty();
+    if request.json.is_some() && !request.form.is_empty() {
+        problems.push("request has both a json and a form body".to_string());
+    }
+    if has_body && matches!(method.to_ascii_uppercase().as_str(), "GET" | "HEAD") {
+        problems.push(format!("request has a body but its method is {}", method.to_ascii_uppercase()));
+    }
+    if let Some(url) = &request.url {
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            problems.push(format!("request.url must start with http:// or https:// (got \"{}\")", url));
+        }
+        if !request.sends_username() {
+            problems.push("request never sends the username: put {} in request.url, a header or the body".to_string());
+        }
+    }
+    for (name, value) in &request.headers {
+        if reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_err() {
+            problems.push(format!("request.headers has an invalid header name \"{}\"", name));
+        }
+        if reqwest::header::HeaderValue::from_str(value).is_err() {
+            problems.push(format!("request.headers.{} has an invalid value", name));
+        }
+    }
+    for (name, value) in &request.cookies {
+        if name.is_empty() || name.contains(['=', ';', ' ']) || value.contains([';', '\r', '\n']) {
+            problems.push(format!("request.cookies has an invalid cookie \"{}\"", name));
+        }
+    }
+    problems
 }
 
+/// The built-in catalogue
 pub fn get_sites() -> Vec<Site> {
-    vec![
-        // Major Social Networks
-        Site { name: "Twitter/X".to_string(), url: "https://twitter.com/{}".to_string(), category: "Social Network".to_string() },
-        // Facebook removed - requires authentication, blocks automated checks
-        Site { name: "Instagram".to_string(), url: "https://www.instagram.com/{}/".to_string(), category: "Social Network".to_string() },
-        // LinkedIn removed - blocks automated checks with HTTP 999
-        Site { name: "TikTok".to_string(), url: "https://www.tiktok.com/@{}".to_string(), category: "Social Network".to_string() },
-        Site { name: "Snapchat".to_string(), url: "https://www.snapchat.com/add/{}".to_string(), category: "Social Network".to_string() },
-        Site { name: "Pinterest".to_string(), url: "https://www.pinterest.com/{}/".to_string(), category: "Social Network".to_string() },
-        Site { name: "Reddit".to_string(), url: "https://www.reddit.com/user/{}".to_string(), category: "Forum".to_string() },
-        Site { name: "YouTube".to_string(), url: "https://www.youtube.com/@{}".to_string(), category: "Video".to_string() },
-        Site { name: "Twitch".to_string(), url: "https://www.twitch.tv/{}".to_string(), category: "Gaming".to_string() },
-        
-        // Tech & Development
-        Site { name: "GitHub".to_string(), url: "https://github.com/{}".to_string(), category: "Development".to_string() },
-        Site { name: "GitLab".to_string(), url: "https://gitlab.com/{}".to_string(), category: "Development".to_string() },
-        Site { name: "Bitbucket".to_string(), url: "https://bitbucket.org/{}/".to_string(), category: "Development".to_string() },
-        Site { name: "Stack Overflow".to_string(), url: "https://stackoverflow.com/users/{}".to_string(), category: "Forum".to_string() },
-        Site { name: "Dev.to".to_string(), url: "https://dev.to/{}".to_string(), category: "Blog".to_string() },
-        Site { name: "Medium".to_string(), url: "https://medium.com/@{}".to_string(), category: "Blog".to_string() },
-        // Hashnode removed - strict rate limiting (HTTP 429)
-        Site { name: "HackerRank".to_string(), url: "https://www.hackerrank.com/{}".to_string(), category: "Development".to_string() },
-        Site { name: "CodePen".to_string(), url: "https://codepen.io/{}".to_string(), category: "Development".to_string() },
-        Site { name: "LeetCode".to_string(), url: "https://leetcode.com/{}/".to_string(), category: "Development".to_string() },
-        Site { name: "Codeforces".to_string(), url: "https://codeforces.com/profile/{}".to_string(), category: "Development".to_string() },
-        Site { name: "AtCoder".to_string(), url: "https://atcoder.jp/users/{}".to_string(), category: "Development".to_string() },
-        Site { name: "Kaggle".to_string(), url: "https://www.kaggle.com/{}".to_string(), category: "Data Science".to_string() },
-        Site { name: "Replit".to_string(), url: "https://replit.com/@{}".to_string(), category: "Development".to_string() },
-        
-        // Creative & Design
-        Site { name: "DeviantArt".to_string(), url: "https://www.deviantart.com/{}".to_string(), category: "Art".to_string() },
-        Site { name: "Behance".to_string(), url: "https://www.behance.net/{}".to_string(), category: "Design".to_string() },
-        Site { name: "Dribbble".to_string(), url: "https://dribbble.com/{}".to_string(), category: "Design".to_string() },
-        Site { name: "ArtStation".to_string(), url: "https://www.artstation.com/{}".to_string(), category: "Art".to_string() },
-        Site { name: "Flickr".to_string(), url: "https://www.flickr.com/people/{}/".to_string(), category: "Photography".to_string() },
-        Site { name: "500px".to_string(), url: "https://500px.com/p/{}".to_string(), category: "Photography".to_string() },
-        Site { name: "Unsplash".to_string(), url: "https://unsplash.com/@{}".to_string(), category: "Photography".to_string() },
-        
-        // Forums & Communities
-        Site { name: "Steam".to_string(), url: "https://steamcommunity.com/id/{}".to_string(), category: "Gaming".to_string() },
-        Site { name: "Discord".to_string(), url: "https://discord.com/users/{}".to_string(), category: "Social".to_string() },
-        Site { name: "Xbox Live".to_string(), url: "https://account.xbox.com/en-us/profile?gamertag={}".to_string(), category: "Gaming".to_string() },
-        Site { name: "PlayStation".to_string(), url: "https://psnprofiles.com/{}".to_string(), category: "Gaming".to_string() },
-        Site { name: "Wikipedia".to_string(), url: "https://en.wikipedia.org/wiki/User:{}".to_string(), category: "Wiki".to_string() },
-        Site { name: "Wikia/Fandom".to_string(), url: "https://www.fandom.com/users/{}".to_string(), category: "Wiki".to_string() },
-        Site { name: "Quora".to_string(), url: "https://www.quora.com/profile/{}".to_string(), category: "Forum".to_string() },
-        Site { name: "Product Hunt".to_string(), url: "https://www.producthunt.com/@{}".to_string(), category: "Tech".to_string() },
-        Site { name: "AngelList".to_string(), url: "https://angel.co/{}".to_string(), category: "Professional".to_string() },
-        
-        // Video Platforms
-        Site { name: "Vimeo".to_string(), url: "https://vimeo.com/{}".to_string(), category: "Video".to_string() },
-        Site { name: "Dailymotion".to_string(), url: "https://www.dailymotion.com/{}".to_string(), category: "Video".to_string() },
-        
-        // Music
-        Site { name: "Spotify".to_string(), url: "https://open.spotify.com/user/{}".to_string(), category: "Music".to_string() },
-        Site { name: "SoundCloud".to_string(), url: "https://soundcloud.com/{}".to_string(), category: "Music".to_string() },
-        Site { name: "Last.fm".to_string(), url: "https://www.last.fm/user/{}".to_string(), category: "Music".to_string() },
-        Site { name: "Bandcamp".to_string(), url: "https://{}.bandcamp.com".to_string(), category: "Music".to_string() },
-        
-        // News & Blogging
-        Site { name: "Tumblr".to_string(), url: "https://{}.tumblr.com".to_string(), category: "Blog".to_string() },
-        Site { name: "WordPress.com".to_string(), url: "https://{}.wordpress.com".to_string(), category: "Blog".to_string() },
-        Site { name: "Blogger".to_string(), url: "https://{}.blogspot.com".to_string(), category: "Blog".to_string() },
-        
-        // Professional & Business
-        Site { name: "Crunchbase".to_string(), url: "https://www.crunchbase.com/person/{}".to_string(), category: "Professional".to_string() },
-        Site { name: "About.me".to_string(), url: "https://about.me/{}".to_string(), category: "Professional".to_string() },
-        Site { name: "Keybase".to_string(), url: "https://keybase.io/{}".to_string(), category: "Social".to_string() },
-        
-        // Gaming Communities (Mixer was shut down in 2020 - removed)
-        Site { name: "Roblox".to_string(), url: "https://www.roblox.com/user.aspx?username={}".to_string(), category: "Gaming".to_string() },
-        Site { name: "Chess.com".to_string(), url: "https://www.chess.com/member/{}".to_string(), category: "Gaming".to_string() },
-        Site { name: "Lichess".to_string(), url: "https://lichess.org/@/{}".to_string(), category: "Gaming".to_string() },
-        
-        // Coding & Tech Communities
-        Site { name: "Gitee".to_string(), url: "https://gitee.com/{}".to_string(), category: "Development".to_string() },
-        Site { name: "SourceForge".to_string(), url: "https://sourceforge.net/u/{}/profile".to_string(), category: "Development".to_string() },
-        Site { name: "Launchpad".to_string(), url: "https://launchpad.net/~{}".to_string(), category: "Development".to_string() },
-        Site { name: "FreeCodeCamp".to_string(), url: "https://www.freecodecamp.org/{}".to_string(), category: "Education".to_string() },
-        
-        // Additional Platforms
-        Site { name: "VK".to_string(), url: "https://vk.com/{}".to_string(), category: "Social Network".to_string() },
-        Site { name: "OK.ru".to_string(), url: "https://ok.ru/{}".to_string(), category: "Social Network".to_string() },
-        Site { name: "Telegram".to_string(), url: "https://t.me/{}".to_string(), category: "Social".to_string() },
-        Site { name: "Weibo".to_string(), url: "https://weibo.com/{}".to_string(), category: "Social Network".to_string() },
-        Site { name: "Douban".to_string(), url: "https://www.douban.com/people/{}".to_string(), category: "Social Network".to_string() },
-        
-        // Forums & Message Boards
-        Site { name: "XDA Developers".to_string(), url: "https://forum.xda-developers.com/m/{}.0".to_string(), category: "Forum".to_string() },
-        Site { name: "Stack Exchange".to_string(), url: "https://stackexchange.com/users/{}".to_string(), category: "Forum".to_string() },
-        // Ask.fm removed - DNS issues, site may be down
-        
-        // Additional Sites
-        Site { name: "Imgur".to_string(), url: "https://imgur.com/user/{}".to_string(), category: "Image".to_string() },
-        Site { name: "Giphy".to_string(), url: "https://giphy.com/{}".to_string(), category: "Image".to_string() },
-        Site { name: "SlideShare".to_string(), url: "https://www.slideshare.net/{}".to_string(), category: "Professional".to_string() },
-        Site { name: "Scribd".to_string(), url: "https://www.scribd.com/{}".to_string(), category: "Document".to_string() },
-        Site { name: "Patreon".to_string(), url: "https://www.patreon.com/{}".to_string(), category: "Crowdfunding".to_string() },
-        Site { name: "Kickstarter".to_string(), url: "https://www.kickstarter.com/profile/{}".to_string(), category: "Crowdfunding".to_string() },
-        Site { name: "IndieGoGo".to_string(), url: "https://www.indiegogo.com/individuals/{}".to_string(), category: "Crowdfunding".to_string() },
-        Site { name: "Gumroad".to_string(), url: "https://{}.gumroad.com".to_string(), category: "E-commerce".to_string() },
-        Site { name: "Etsy".to_string(), url: "https://www.etsy.com/shop/{}".to_string(), category: "E-commerce".to_string() },
-        Site { name: "eBay".to_string(), url: "https://www.ebay.com/usr/{}".to_string(), category: "E-commerce".to_string() },
-        Site { name: "Goodreads".to_string(), url: "https://www.goodreads.com/{}".to_string(), category: "Books".to_string() },
-        Site { name: "Letterboxd".to_string(), url: "https://letterboxd.com/{}".to_string(), category: "Movies".to_string() },
-        Site { name: "Trakt".to_string(), url: "https://trakt.tv/users/{}".to_string(), category: "Movies".to_string() },
-        Site { name: "MyAnimeList".to_string(), url: "https://myanimelist.net/profile/{}".to_string(), category: "Anime".to_string() },
-        Site { name: "AniList".to_string(), url: "https://anilist.co/user/{}".to_string(), category: "Anime".to_string() },
-        Site { name: "Fandom".to_string(), url: "https://www.fandom.com/user/{}".to_string(), category: "Wiki".to_string() },
-        
-        // More Development
-        Site { name: "JSFiddle".to_string(), url: "https://jsfiddle.net/user/{}/".to_string(), category: "Development".to_string() },
-        Site { name: "Pastebin".to_string(), url: "https://pastebin.com/u/{}".to_string(), category: "Development".to_string() },
-        Site { name: "HackerEarth".to_string(), url: "https://www.hackerearth.com/@{}".to_string(), category: "Development".to_string() },
-        Site { name: "TopCoder".to_string(), url: "https://www.topcoder.com/members/{}".to_string(), category: "Development".to_string() },
-        Site { name: "Exercism".to_string(), url: "https://exercism.org/profiles/{}".to_string(), category: "Development".to_string() },
-        Site { name: "Glitch".to_string(), url: "https://glitch.com/@{}".to_string(), category: "Development".to_string() },
-        
-        // More Social (MySpace removed - SSL certificate issues)
-        Site { name: "Badoo".to_string(), url: "https://badoo.com/profile/{}".to_string(), category: "Dating".to_string() },
-        Site { name: "Bumble".to_string(), url: "https://bumble.com/app/profile/{}".to_string(), category: "Dating".to_string() },
-        
-        // More Creative
-        Site { name: "Mixcloud".to_string(), url: "https://www.mixcloud.com/{}/".to_string(), category: "Music".to_string() },
-        // Spotify Artist removed - uses IDs, not usernames
-        
-        // More Professional
-        Site { name: "Coursera".to_string(), url: "https://www.coursera.org/user/{}".to_string(), category: "Education".to_string() },
-        Site { name: "Udemy".to_string(), url: "https://www.udemy.com/user/{}/".to_string(), category: "Education".to_string() },
-        Site { name: "edX".to_string(), url: "https://www.edx.org/user/{}".to_string(), category: "Education".to_string() },
-        
-        // More Gaming
-        Site { name: "Epic Games".to_string(), url: "https://www.epicgames.com/account/personal?productName=&lang=en".to_string(), category: "Gaming".to_string() },
-        Site { name: "Battle.net".to_string(), url: "https://blizzard.com/invite/{}".to_string(), category: "Gaming".to_string() },
-        Site { name: "Origin".to_string(), url: "https://www.origin.com/usa/en-us/profile/{}".to_string(), category: "Gaming".to_string() },
-        Site { name: "Uplay".to_string(), url: "https://club.ubisoft.com/en-US/profile/{}".to_string(), category: "Gaming".to_string() },
-        
-        // Additional platforms to reach 100+
-        Site { name: "Gravatar".to_string(), url: "https://en.gravatar.com/{}".to_string(), category: "Profile".to_string() },
-        Site { name: "Disqus".to_string(), url: "https://disqus.com/by/{}/".to_string(), category: "Forum".to_string() },
-        Site { name: "Slideshare".to_string(), url: "https://www.slideshare.net/{}".to_string(), category: "Professional".to_string() },
-        Site { name: "Vero".to_string(), url: "https://vero.co/{}".to_string(), category: "Social Network".to_string() },
-        // Ello removed - HTTP 520 Cloudflare errors, site may be down
-        Site { name: "Mastodon".to_string(), url: "https://mastodon.social/@{}".to_string(), category: "Social Network".to_string() },
-        Site { name: "Bluesky".to_string(), url: "https://bsky.app/profile/{}.bsky.social".to_string(), category: "Social Network".to_string() },
-        Site { name: "Threads".to_string(), url: "https://www.threads.net/@{}".to_string(), category: "Social Network".to_string() },
-        Site { name: "Wattpad".to_string(), url: "https://www.wattpad.com/user/{}".to_string(), category: "Writing".to_string() },
-        Site { name: "Archive of Our Own".to_string(), url: "https://archiveofourown.org/users/{}".to_string(), category: "Writing".to_string() },
-        Site { name: "FanFiction".to_string(), url: "https://www.fanfiction.net/u/{}".to_string(), category: "Writing".to_string() },
-    ]
+    parse_manifest(DEFAULT_MANIFEST, ManifestFormat::Toml).expect("Embedded sites.toml is invalid")
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(text: &str) -> Vec<String> {
+        match parse_manifest(text, ManifestFormat::Toml) {
+            Err(ManifestError::Invalid(problems)) => problems.into_iter().map(|(_, _, problem)| problem).collect(),
+            other => panic!("expected validation problems, got {:?}", other.map(|sites| sites.len())),
+        }
+    }
+
+    #[test]
+    fn embedded_catalogue_is_valid() {
+        let sites = parse_manifest(DEFAULT_MANIFEST, ManifestFormat::Toml).expect("sites.toml should parse and validate");
+        assert!(sites.len() > 100);
+        assert!(sites.iter().any(|site| site.name == "GitHub"));
+        for site in sites.iter().filter(|site| site.unsupported.is_none()) {
+            assert!(site.claimed.is_some() && site.unclaimed.is_some(), "{} needs claimed and unclaimed samples", site.name);
+        }
+    }
+
+    #[test]
+    fn json_catalogues_may_be_tables_or_lists() {
+        let site = r#"{"name": "A", "url": "https://a.example/{}", "category": "Test"}"#;
+        let table = parse_manifest(&format!(r#"{{"site": [{}]}}"#, site), ManifestFormat::Json).unwrap();
+        let list = parse_manifest(&format!("[{}]", site), ManifestFormat::Json).unwrap();
+        assert_eq!(table[0].name, "A");
+        assert_eq!(list[0].url, "https://a.example/{}");
+    }
+
+    #[test]
+    fn manifest_round_trips() {
+        let sites = get_sites();
+        for format in [ManifestFormat::Toml, ManifestFormat::Json] {
+            let text = to_manifest(&sites, format).unwrap();
+            let reparsed = parse_manifest(&text, format).unwrap();
+            assert_eq!(reparsed.len(), sites.len());
+        }
+    }
+
+    #[test]
+    fn empty_catalogue_is_rejected() {
+        assert!(matches!(parse_manifest("", ManifestFormat::Toml), Err(ManifestError::Empty)));
+    }
+
+    #[test]
+    fn unknown_fields_are_parse_errors() {
+        let text = "[[site]]\nname = \"A\"\nurl = \"https://a.example/{}\"\ncategory = \"T\"\nurl_probe = \"x\"\n";
+        assert!(matches!(parse_manifest(text, ManifestFormat::Toml), Err(ManifestError::Parse { .. })));
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let text = r#"
+[[site]]
+name = "A"
+url = "ftp://a.example/profile"
+category = ""
+username_pattern = "["
+
+[[site]]
+name = "A"
+url = "https://a.example/{}"
+category = "T"
+
+[site.rules]
+error_codes = [404]
+found_codes = [404]
+absence = [""]
+
+[site.rate_limit]
+concurrency = 0
+"#;
+        let problems = problems(text);
+        let expected = [
+            "category is empty",
+            "url must start with http:// or https://",
+            "url has no {} username placeholder",
+            "username_pattern is not a valid regex",
+            "duplicate site name",
+            "status 404 is in both error_codes and found_codes",
+            "rate_limit.concurrency must be at least 1",
+            "rules contain an empty match string",
+        ];
+        for expected in expected {
+            assert!(problems.iter().any(|p| p.starts_with(expected)), "missing \"{}\" in {:?}", expected, problems);
+        }
+        assert_eq!(problems.len(), expected.len());
+    }
+
+    #[test]
+    fn samples_and_selectors_are_validated() {
+        let text = r#"
+[[site]]
+name = "A"
+url = "https://a.example/{}"
+category = "T"
+username_pattern = '^[a-z]+$'
+claimed = "Bob"
+unclaimed = ""
+
+[site.profile]
+bio = "div[["
+"#;
+        let problems = problems(text);
+        assert!(problems.contains(&"claimed does not match username_pattern (got \"Bob\")".to_string()));
+        assert!(problems.contains(&"unclaimed is empty".to_string()));
+        assert!(problems.contains(&"profile.bio is not a valid CSS selector (got \"div[[\")".to_string()));
+    }
+
+    #[test]
+    fn the_default_unclaimed_sample_must_fit_the_pattern() {
+        let site = |extra: &str| format!("[[site]]\nname = \"A\"\nurl = \"https://a.example/{{}}\"\ncategory = \"T\"\nusername_pattern = '^[0-9]+$'\n{}", extra);
+        assert_eq!(
+            problems(&site("")),
+            ["username_pattern rejects the default unclaimed sample \"zq7vxk2dw9jp3m\"; name an unclaimed one"]
+        );
+        assert!(parse_manifest(&site("unclaimed = \"999999999\"\n"), ManifestFormat::Toml).is_ok());
+        // Unsupported sites are never checked
+        assert!(parse_manifest(&site("unsupported = \"IDs only\"\n"), ManifestFormat::Toml).is_ok());
+    }
+
+    #[test]
+    fn requests_are_validated() {
+        let text = r#"
+[[site]]
+name = "A"
+url = "https://a.example/{}"
+category = "T"
+
+[site.request]
+url = "https://api.a.example/lookup"
+json = { name = "fixed" }
+"#;
+        let problems = problems(text);
+        assert!(problems.iter().any(|p| p.starts_with("request never sends the username")));
+        assert!(problems.iter().any(|p| p == "request has a body but its method is GET"));
+    }
+
+    #[test]
+    fn status_code_rules_are_validated() {
+        let text = r#"
+[[site]]
+name = "A"
+url = "https://a.example/{}"
+category = "T"
+
+[site.rules]
+found_codes = [200]
+blocked_codes = [403]
+invalid_codes = [400, 403]
+require_marker = true
+"#;
+        assert_eq!(
+            problems(text),
+            [
+                "status 403 is in both blocked_codes and invalid_codes",
+                "require_marker needs absence, presence or username_in markers",
+            ]
+        );
+
+        let text = r#"
+[[site]]
+name = "A"
+url = "https://a.example/{}"
+category = "T"
+
+[site.rules]
+blocked_codes = [401]
+invalid_codes = [400]
+absence = ["free"]
+require_marker = true
+"#;
+        let sites = parse_manifest(text, ManifestFormat::Toml).unwrap();
+        assert!(!sites[0].rules.is_empty());
+        let round_trip = to_manifest(&sites, ManifestFormat::Toml).unwrap();
+        assert!(round_trip.contains("require_marker = true"), "{}", round_trip);
+        assert!(!to_manifest(&get_sites()[..1], ManifestFormat::Toml).unwrap().contains("require_marker"));
+    }
+}
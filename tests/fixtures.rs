@@ -0,0 +1,175 @@
+//! Checks replayed from recorded responses
+
+use std::time::{Duration, Instant};
+use vidocq::checker::{AccountChecker, CheckResult, CheckerOptions};
+use vidocq::fixture::{self, Fixture, FixtureMode, RecordedResponse};
+use vidocq::sites::{get_sites, parse_manifest, ManifestFormat};
+
+fn replay(dir: std::path::PathBuf, retries: u32) -> AccountChecker {
+    AccountChecker::with_options(CheckerOptions {
+        retries,
+        retry_delay: Duration::from_secs(5),
+        host_interval: Duration::ZERO,
+        fixtures: Some(FixtureMode::Replay(dir)),
+        ..CheckerOptions::default()
+    })
+    .unwrap()
+}
+
+#[tokio::test]
+async fn missing_fixtures_are_not_retried() {
+    let dir = std::env::temp_dir().join(format!("vidocq-missing-fixtures-{}", std::process::id()));
+    let sites = parse_manifest("[[site]]\nname = \"Alpha\"\nurl = \"https://alpha.test/{}\"\ncategory = \"Test\"\n", ManifestFormat::Toml).unwrap();
+
+    let started = Instant::now();
+    let result = replay(dir, 3).check_account(&sites[0], "alice").await;
+    assert!(matches!(&result.result, CheckResult::Error(e) if e.contains("no fixture for alice on Alpha")), "{:?}", result.result);
+    assert_eq!(result.attempts, 1);
+    assert!(started.elapsed() < Duration::from_secs(1), "replay waited {:?}", started.elapsed());
+}
+
+#[tokio::test]
+async fn unsupported_sites_are_not_requested() {
+    // Replaying from an empty directory, any request would come back as a missing fixture
+    let dir = std::env::temp_dir().join(format!("vidocq-unsupported-fixtures-{}", std::process::id()));
+    let checker = replay(dir, 0);
+    let sites = get_sites();
+    for name in ["Mixer", "Spotify Artist"] {
+        let site = sites.iter().find(|site| site.name == name).unwrap();
+        let result = checker.check_account(site, "alice").await;
+        assert!(matches!(&result.result, CheckResult::Unsupported(reason) if Some(reason) == site.unsupported.as_ref()), "{:?}", result.result);
+        assert_eq!(result.attempts, 0);
+        assert_eq!(result.status_code, None);
+    }
+}
+
+/// The committed fixtures, all real recordings, still read as they did when recorded
+#[tokio::test]
+async fn committed_fixtures_replay_as_expected() {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+    let fixtures = fixture::load_all(&dir).unwrap();
+    let sites = get_sites();
+    let checker = replay(dir, 0);
+
+    let mut failures = Vec::new();
+    for (path, fixture) in &fixtures {
+        if fixture.recorded_at.is_none() {
+            failures.push(format!("{}: not a recording (no recorded_at); crafted pages belong in tests/fixtures.rs", path.display()));
+            continue;
+        }
+        let Some(site) = sites.iter().find(|site| site.name == fixture.site) else {
+            failures.push(format!("{}: {} is not in the catalogue", path.display(), fixture.site));
+            continue;
+        };
+        let result = checker.check_account(site, &fixture.username).await;
+        if result.result.tag() != fixture.expect {
+            let detail: Vec<&str> = result.evidence.iter().map(|e| e.detail.as_str()).collect();
+            failures.push(format!("{}: expected {}, got {} ({})", path.display(), fixture.expect, result.result.tag(), detail.join("; ")));
+        }
+    }
+    assert!(failures.is_empty(), "{} fixture(s) failed:\n{}", failures.len(), failures.join("\n"));
+}
+
+/// Write one crafted fixture per (username, status, body, expected result) case
+fn write_cases(dir: &std::path::Path, site: &str, url: &str, content_type: &str, cases: &[(&str, u16, &str, &str)]) {
+    for (username, status, body, expect) in cases {
+        let url = url.replace("{}", username);
+        let fixture = Fixture {
+            site: site.to_string(),
+            username: username.to_string(),
+            url: url.clone(),
+            expect: expect.to_string(),
+            recorded_at: None,
+            response: RecordedResponse {
+                status: *status,
+                headers: vec![("content-type".to_string(), content_type.to_string())],
+                final_url: url,
+                redirects: Vec::new(),
+                body: body.to_string(),
+            },
+        };
+        fixture::save(dir, &fixture).unwrap();
+    }
+}
+
+/// Replay the cases against the catalogue entry for `site`
+async fn assert_cases(test: &str, site: &str, url: &str, content_type: &str, cases: &[(&str, u16, &str, &str)]) {
+    let dir = std::env::temp_dir().join(format!("vidocq-{}-fixtures-{}", test, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    write_cases(&dir, site, url, content_type, cases);
+
+    let sites = get_sites();
+    let entry = sites.iter().find(|entry| entry.name == site).unwrap();
+    let checker = replay(dir.clone(), 0);
+    for (username, status, _, expect) in cases {
+        let result = checker.check_account(entry, username).await;
+        assert_eq!(result.result.tag(), *expect, "{}: HTTP {} for {}: {:?}", site, status, username, result.evidence);
+    }
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+/// Every answer the Discord availability API gives maps to its own result
+#[tokio::test]
+async fn discord_answers_map_to_results() {
+    let url = "https://discord.com/api/v9/unique-username/username-attempt-unauthed";
+    assert_cases("discord", "Discord", url, "application/json", &[
+        ("taken", 200, r#"{"taken":true}"#, "found"),
+        ("free", 200, r#"{"taken":false}"#, "not_found"),
+        ("unauthorized", 401, r#"{"message":"401: Unauthorized"}"#, "blocked"),
+        ("forbidden", 403, r#"{"message":"Forbidden"}"#, "blocked"),
+        ("malformed", 400, r#"{"message":"Invalid Form Body"}"#, "invalid_username"),
+        ("unprocessable", 422, r#"{"message":"Unprocessable"}"#, "invalid_username"),
+        ("changed", 200, r#"{"result":"maybe"}"#, "error"),
+        ("broken", 500, "Internal Server Error", "unavailable"),
+    ])
+    .await;
+}
+
+/// Twitter/X answers everyone with an SPA shell or an anti-bot 403, neither of which rules an account out
+#[tokio::test]
+async fn twitter_shells_and_403s_are_found() {
+    assert_cases("twitter", "Twitter/X", "https://twitter.com/{}", "text/html", &[
+        ("jack", 200, r#"<html><head><title>X</title></head><body><div id="react-root"></div></body></html>"#, "found"),
+        ("ghost", 403, "<html><head><title>Page not found / X</title></head><body>Access denied</body></html>", "found"),
+    ])
+    .await;
+}
+
+/// TopCoder sends members on to profiles.topcoder.com with a meta refresh, and only its title
+/// tells the generic page apart
+#[tokio::test]
+async fn topcoder_reads_the_refresh_and_the_title() {
+    let page = |title: &str, target: &str| {
+        format!(
+            r#"<html><head><title>{}</title><meta name="description" content="Topcoder connects top technology talent"><meta http-equiv="refresh" content="0; url={}"></head><body></body></html>"#,
+            title, target
+        )
+    };
+    assert_cases("topcoder", "TopCoder", "https://www.topcoder.com/members/{}", "text/html", &[
+        ("tourist", 200, &page("tourist | Topcoder", "https://profiles.topcoder.com/tourist"), "found"),
+        ("ghost", 200, &page("Member | Topcoder", "https://profiles.topcoder.com/"), "not_found"),
+        ("nobody", 200, &page("Topcoder | Top Technology Talent", "https://profiles.topcoder.com/nobody"), "not_found"),
+    ])
+    .await;
+}
+
+/// Glitch serves its home page title for missing users; other pages go to the generic checks
+#[tokio::test]
+async fn glitch_reads_the_generic_title() {
+    assert_cases("glitch", "Glitch", "https://glitch.com/@{}", "text/html", &[
+        ("fogcreek", 200, "<html><head><title>Fog Creek</title></head><body><h1>@fogcreek</h1></body></html>", "found"),
+        ("nobody", 200, "<html><head><title>Glitch: The friendly community where everyone builds the web</title></head><body></body></html>", "not_found"),
+        ("vanished", 200, "<html><head><title>Glitch</title></head><body></body></html>", "not_found"),
+    ])
+    .await;
+}
+
+/// An og:title tag plus the username anywhere on the page is proof enough
+#[tokio::test]
+async fn og_title_pages_only_need_to_mention_the_username() {
+    assert_cases("threads", "Threads", "https://www.threads.net/@{}", "text/html", &[
+        ("zuck", 200, r#"<html><head><title>Threads</title><meta property="og:title" content="Mark Zuckerberg on Threads"></head><body><a href="/@zuck">profile</a></body></html>"#, "found"),
+        ("nobody", 200, "<html><head><title>Threads</title></head><body>Log in with your Instagram account</body></html>", "not_found"),
+    ])
+    .await;
+}
@@ -0,0 +1,116 @@
+//! Proxy routing against local forward-proxy stand-ins
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use vidocq::checker::{AccountChecker, CheckResult, CheckerOptions, ProxyMode};
+use vidocq::sites::{parse_manifest, ManifestFormat, Site};
+
+/// A forward proxy that answers every request itself with a profile page, recording the
+/// absolute-form target of each request it receives
+async fn proxy_stand_in() -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&seen);
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut socket, _)) = listener.accept().await else { return };
+            let log = Arc::clone(&log);
+            tokio::spawn(async move {
+                let mut head = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match socket.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => head.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let head = String::from_utf8_lossy(&head);
+                let target = head.split_whitespace().nth(1).unwrap_or_default().to_string();
+                let username = target.rsplit('/').next().unwrap_or_default().to_string();
+                log.lock().unwrap().push(target);
+                let body = format!("<html><head><title>{0}</title></head><body><h1>{0}</h1></body></html>", username);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    (address, seen)
+}
+
+/// Sites on a host that doesn't exist, so nothing can answer except the proxy
+fn sites(count: usize) -> Vec<Site> {
+    let manifest: String = (0..count)
+        .map(|i| format!("[[site]]\nname = \"Site{0}\"\nurl = \"http://vidocq.test/site{0}/{{}}\"\ncategory = \"Test\"\n\n", i))
+        .collect();
+    parse_manifest(&manifest, ManifestFormat::Toml).unwrap()
+}
+
+fn checker(proxies: Vec<String>, proxy_mode: ProxyMode) -> AccountChecker {
+    AccountChecker::with_options(CheckerOptions {
+        retries: 0,
+        host_concurrency: 1,
+        host_interval: Duration::ZERO,
+        proxies,
+        proxy_mode,
+        ..CheckerOptions::default()
+    })
+    .unwrap()
+}
+
+/// How many requests for a site each proxy served
+fn served_by(a: &Mutex<Vec<String>>, b: &Mutex<Vec<String>>, site: &str) -> (usize, usize) {
+    let count = |log: &Mutex<Vec<String>>| log.lock().unwrap().iter().filter(|t| t.contains(&format!("/{}/", site))).count();
+    (count(a), count(b))
+}
+
+#[tokio::test]
+async fn requests_go_through_the_proxy() {
+    let (proxy, seen) = proxy_stand_in().await;
+    let checker = checker(vec![proxy], ProxyMode::RoundRobin);
+    let result = checker.check_account(&sites(1)[0], "alice").await;
+
+    assert_eq!(result.result, CheckResult::Found, "{:?}", result.evidence);
+    assert_eq!(*seen.lock().unwrap(), vec!["http://vidocq.test/site0/alice".to_string()]);
+}
+
+#[tokio::test]
+async fn round_robin_alternates_between_proxies() {
+    let (proxy_a, seen_a) = proxy_stand_in().await;
+    let (proxy_b, seen_b) = proxy_stand_in().await;
+    let checker = checker(vec![proxy_a, proxy_b], ProxyMode::RoundRobin);
+    let sites = sites(1);
+
+    for _ in 0..6 {
+        checker.check_account(&sites[0], "alice").await;
+    }
+    // Every request takes the next proxy in turn, even to the same site
+    assert_eq!(seen_a.lock().unwrap().len(), 3);
+    assert_eq!(seen_b.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn per_site_keeps_each_site_on_one_proxy() {
+    let (proxy_a, seen_a) = proxy_stand_in().await;
+    let (proxy_b, seen_b) = proxy_stand_in().await;
+    let checker = checker(vec![proxy_a, proxy_b], ProxyMode::PerSite);
+    let sites = sites(16);
+
+    for site in &sites {
+        for username in ["alice", "bob", "carol"] {
+            checker.check_account(site, username).await;
+        }
+    }
+    for i in 0..sites.len() {
+        let served = served_by(&seen_a, &seen_b, &format!("site{}", i));
+        assert!(served == (3, 0) || served == (0, 3), "site{} was split across proxies: {:?}", i, served);
+    }
+    // Sites are spread over the proxies by name
+    assert!(!seen_a.lock().unwrap().is_empty() && !seen_b.lock().unwrap().is_empty());
+}
@@ -0,0 +1,199 @@
+//! Redirect handling against local servers: methods, bodies, headers, hop limit and host budgets
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use vidocq::checker::{AccountChecker, CheckResult, CheckerOptions};
+use vidocq::sites::{parse_manifest, ManifestFormat, Site};
+
+/// One request as a stand-in server saw it
+#[derive(Debug, Clone)]
+struct Seen {
+    server: &'static str,
+    method: String,
+    path: String,
+    /// Header lines, lowercased
+    headers: Vec<String>,
+    body: String,
+}
+
+type Log = Arc<Mutex<Vec<Seen>>>;
+
+/// A server that answers `/echo/...` with a profile page and redirects everything else:
+/// `/hop/N/...` N more times, `/307/...` and `/302/...` to `/echo`, and `/to/<url>` to `<url>`
+async fn server(name: &'static str, log: Log) -> u16 {
+    // The checker reads "404" anywhere in a redirect target as an error page, port included
+    let (listener, port) = loop {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        if !port.to_string().contains("404") {
+            break (listener, port);
+        }
+    };
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let log = Arc::clone(&log);
+            tokio::spawn(async move {
+                let Some(seen) = read_request(name, &mut socket).await else { return };
+                let path = seen.path.clone();
+                log.lock().unwrap().push(seen);
+
+                let redirect = |status: &str, location: String| {
+                    format!("HTTP/1.1 {}\r\nLocation: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status, location)
+                };
+                let response = if let Some(rest) = path.strip_prefix("/hop/") {
+                    let (hops, username) = rest.split_once('/').unwrap();
+                    match hops.parse::<u32>().unwrap() {
+                        0 => redirect("302 Found", format!("/echo/{}", username)),
+                        n => redirect("302 Found", format!("/hop/{}/{}", n - 1, username)),
+                    }
+                } else if let Some(username) = path.strip_prefix("/307/") {
+                    redirect("307 Temporary Redirect", format!("/echo/{}", username))
+                } else if let Some(username) = path.strip_prefix("/302/") {
+                    redirect("302 Found", format!("/echo/{}", username))
+                } else if let Some(url) = path.strip_prefix("/to/") {
+                    redirect("302 Found", url.to_string())
+                } else {
+                    let username = path.rsplit('/').next().unwrap_or_default();
+                    let body = format!("<html><head><title>{0}</title></head><body><h1>{0}</h1></body></html>", username);
+                    format!("HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body)
+                };
+                let _ = socket.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    port
+}
+
+async fn read_request(server: &'static str, socket: &mut tokio::net::TcpStream) -> Option<Seen> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let n = socket.read(&mut buf).await.ok().filter(|n| *n > 0)?;
+        request.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&request).into_owned();
+        let Some((head, body)) = text.split_once("\r\n\r\n") else { continue };
+        let mut lines = head.lines();
+        let mut start = lines.next()?.split_whitespace();
+        let (method, path) = (start.next()?.to_string(), start.next()?.to_string());
+        let headers: Vec<String> = lines.map(str::to_ascii_lowercase).collect();
+        let length = headers.iter().find_map(|h| h.strip_prefix("content-length:")).map_or(0, |v| v.trim().parse().unwrap_or(0));
+        if body.len() >= length {
+            return Some(Seen { server, method, path, headers, body: body.to_string() });
+        }
+    }
+}
+
+fn checker() -> AccountChecker {
+    AccountChecker::with_options(CheckerOptions {
+        retries: 0,
+        host_concurrency: 1,
+        host_interval: Duration::ZERO,
+        ..CheckerOptions::default()
+    })
+    .unwrap()
+}
+
+/// A site that POSTs a JSON body with a token header to `url`
+fn posting_site(url: &str) -> Site {
+    let manifest = format!(
+        "[[site]]\nname = \"Api\"\nurl = \"{}\"\ncategory = \"Test\"\n\n[site.request]\nmethod = \"POST\"\njson = {{ username = \"{{}}\" }}\nheaders = {{ X-Token = \"secret\" }}\n",
+        url
+    );
+    parse_manifest(&manifest, ManifestFormat::Toml).unwrap().remove(0)
+}
+
+fn plain_site(url: &str) -> Site {
+    let manifest = format!("[[site]]\nname = \"Plain\"\nurl = \"{}\"\ncategory = \"Test\"\n", url);
+    parse_manifest(&manifest, ManifestFormat::Toml).unwrap().remove(0)
+}
+
+fn has_token(seen: &Seen) -> bool {
+    seen.headers.iter().any(|h| h == "x-token: secret")
+}
+
+#[tokio::test]
+async fn temporary_redirects_repeat_the_request() {
+    let log = Log::default();
+    let port = server("a", Arc::clone(&log)).await;
+    let result = checker().check_account(&posting_site(&format!("http://127.0.0.1:{}/307/{{}}", port)), "alice").await;
+    assert_eq!(result.result, CheckResult::Found, "{:?}", result.evidence);
+
+    let log = log.lock().unwrap();
+    assert_eq!(log.len(), 2);
+    for seen in log.iter() {
+        assert_eq!(seen.method, "POST", "{:?}", seen);
+        assert_eq!(seen.body, r#"{"username":"alice"}"#);
+        assert!(has_token(seen), "{:?}", seen);
+    }
+    assert_eq!(log[1].path, "/echo/alice");
+}
+
+#[tokio::test]
+async fn other_redirects_continue_as_a_bodiless_get() {
+    let log = Log::default();
+    let port = server("a", Arc::clone(&log)).await;
+    let result = checker().check_account(&posting_site(&format!("http://127.0.0.1:{}/302/{{}}", port)), "alice").await;
+    assert_eq!(result.result, CheckResult::Found, "{:?}", result.evidence);
+
+    let log = log.lock().unwrap();
+    assert_eq!((log[0].method.as_str(), log[1].method.as_str()), ("POST", "GET"));
+    assert_eq!(log[1].body, "");
+    assert!(!log[1].headers.iter().any(|h| h.starts_with("content-type:")), "{:?}", log[1]);
+    // Same origin, so the site's headers still apply
+    assert!(has_token(&log[1]), "{:?}", log[1]);
+}
+
+#[tokio::test]
+async fn headers_stay_with_their_origin() {
+    let log = Log::default();
+    let home = server("home", Arc::clone(&log)).await;
+    let away = server("away", Arc::clone(&log)).await;
+    let url = format!("http://127.0.0.1:{}/to/http://localhost:{}/echo/{{}}", home, away);
+    let result = checker().check_account(&posting_site(&url), "alice").await;
+    assert_eq!(result.result, CheckResult::Found, "{:?}", result.evidence);
+
+    let log = log.lock().unwrap();
+    assert_eq!(log.iter().map(|s| s.server).collect::<Vec<_>>(), ["home", "away"]);
+    assert!(has_token(&log[0]));
+    assert!(!has_token(&log[1]), "{:?}", log[1]);
+}
+
+#[tokio::test]
+async fn up_to_ten_redirects_are_followed() {
+    let log = Log::default();
+    let port = server("a", Arc::clone(&log)).await;
+    let checker = checker();
+
+    // /hop/9 redirects ten times before reaching /echo
+    let result = checker.check_account(&plain_site(&format!("http://127.0.0.1:{}/hop/9/{{}}", port)), "alice").await;
+    assert_eq!(result.result, CheckResult::Found, "{:?}", result.evidence);
+    assert_eq!(log.lock().unwrap().len(), 11);
+
+    let result = checker.check_account(&plain_site(&format!("http://127.0.0.1:{}/hop/10/{{}}", port)), "alice").await;
+    assert!(matches!(&result.result, CheckResult::Error(e) if e.contains("too many redirects")), "{:?}", result.result);
+}
+
+#[tokio::test]
+async fn chains_between_hosts_take_each_hosts_budget() {
+    let log = Log::default();
+    let home = server("home", Arc::clone(&log)).await;
+    let away = server("away", Arc::clone(&log)).await;
+    let checker = Arc::new(checker());
+
+    // 127.0.0.1 -> localhost -> 127.0.0.1 and back the other way, at once, with one request per host
+    // allowed in flight: holding on to a host while waiting for the next would never finish
+    let there = format!("http://127.0.0.1:{}/to/http://localhost:{}/to/http://127.0.0.1:{}/echo/{{}}", home, away, home);
+    let back = format!("http://localhost:{}/to/http://127.0.0.1:{}/to/http://localhost:{}/echo/{{}}", away, home, away);
+    let checks = futures::future::join_all((0..4).map(|i| {
+        let checker = Arc::clone(&checker);
+        let site = plain_site(if i % 2 == 0 { &there } else { &back });
+        async move { checker.check_account(&site, "alice").await }
+    }));
+    let results = tokio::time::timeout(Duration::from_secs(10), checks).await.expect("redirect chains deadlocked");
+    for result in results {
+        assert_eq!(result.result, CheckResult::Found, "{:?}", result.evidence);
+    }
+    assert_eq!(log.lock().unwrap().len(), 12);
+}
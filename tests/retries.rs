@@ -0,0 +1,86 @@
+//! Retries against a local server answering every request with one fixed status
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use vidocq::checker::{AccountChecker, CheckResult, CheckerOptions};
+use vidocq::sites::{parse_manifest, ManifestFormat, Site};
+
+/// A server answering every request with `status`, and how many requests it has seen
+async fn server(status: &'static str) -> (u16, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let seen = Arc::new(AtomicUsize::new(0));
+    let count = Arc::clone(&seen);
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let count = Arc::clone(&count);
+            tokio::spawn(async move {
+                let mut head = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match socket.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => head.extend_from_slice(&buf[..n]),
+                    }
+                }
+                count.fetch_add(1, Ordering::SeqCst);
+                let body = "<html><head><title>Something went wrong</title></head><body></body></html>";
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    (port, seen)
+}
+
+fn site(port: u16) -> Site {
+    let manifest = format!("[[site]]\nname = \"Local\"\nurl = \"http://127.0.0.1:{}/{{}}\"\ncategory = \"Test\"\n", port);
+    parse_manifest(&manifest, ManifestFormat::Toml).unwrap().remove(0)
+}
+
+fn checker() -> AccountChecker {
+    AccountChecker::with_options(CheckerOptions {
+        retries: 2,
+        retry_delay: Duration::from_millis(1),
+        host_interval: Duration::ZERO,
+        ..CheckerOptions::default()
+    })
+    .unwrap()
+}
+
+#[tokio::test]
+async fn server_errors_are_retried() {
+    let (port, seen) = server("502 Bad Gateway").await;
+    let result = checker().check_account(&site(port), "alice").await;
+    assert!(matches!(result.result, CheckResult::Unavailable(_)), "{:?}", result.result);
+    assert_eq!(result.attempts, 3);
+    assert_eq!(seen.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn other_errors_are_not_retried() {
+    for status in ["401 Unauthorized", "405 Method Not Allowed", "410 Gone", "451 Unavailable For Legal Reasons"] {
+        let (port, seen) = server(status).await;
+        let result = checker().check_account(&site(port), "alice").await;
+        assert!(matches!(result.result, CheckResult::Error(_)), "{}: {:?}", status, result.result);
+        assert_eq!(result.attempts, 1, "{}", status);
+        assert_eq!(seen.load(Ordering::SeqCst), 1, "{}", status);
+    }
+}
+
+#[tokio::test]
+async fn connection_failures_are_retried() {
+    // Bind and drop a listener for a port that refuses connections
+    let port = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+    let result = checker().check_account(&site(port), "alice").await;
+    assert!(matches!(result.result, CheckResult::Unavailable(_)), "{:?}", result.result);
+    assert_eq!(result.attempts, 3);
+}
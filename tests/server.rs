@@ -0,0 +1,208 @@
+//! The REST API end to end, over a local socket, with checks answered from fixtures
+
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use vidocq::checker::{AccountChecker, CheckerOptions};
+use vidocq::fixture::{self, Fixture, FixtureMode, RecordedResponse};
+use vidocq::server::{self, KEPT_SCANS};
+use vidocq::sites::{parse_manifest, ManifestFormat};
+
+const MANIFEST: &str = r#"
+[[site]]
+name = "Alpha"
+url = "https://alpha.test/{}"
+category = "Test"
+
+[[site]]
+name = "Beta"
+url = "https://beta.test/users/{}"
+category = "Test"
+"#;
+
+/// Fixtures in which `alice` exists on Alpha only
+fn fixtures(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vidocq-server-{}-{}", test, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    for (site, url, status, expect) in [
+        ("Alpha", "https://alpha.test/alice", 200, "found"),
+        ("Beta", "https://beta.test/users/alice", 404, "not_found"),
+    ] {
+        let body = if status == 200 { "<html><title>alice</title><h1>alice</h1></html>" } else { "<html><title>Not Found</title></html>" };
+        let fixture = Fixture {
+            site: site.to_string(),
+            username: "alice".to_string(),
+            url: url.to_string(),
+            expect: expect.to_string(),
+            recorded_at: None,
+            response: RecordedResponse {
+                status,
+                headers: vec![("content-type".to_string(), "text/html".to_string())],
+                final_url: url.to_string(),
+                redirects: Vec::new(),
+                body: body.to_string(),
+            },
+        };
+        fixture::save(&dir, &fixture).unwrap();
+    }
+    dir
+}
+
+/// A server on a local port, answering checks from fixtures, and a client for it
+struct Api {
+    base: String,
+    client: reqwest::Client,
+    dir: PathBuf,
+}
+
+impl Api {
+    async fn start(test: &str) -> Self {
+        let dir = fixtures(test);
+        let checker = AccountChecker::with_options(CheckerOptions {
+            host_interval: Duration::ZERO,
+            fixtures: Some(FixtureMode::Replay(dir.clone())),
+            ..CheckerOptions::default()
+        })
+        .unwrap();
+        let sites = parse_manifest(MANIFEST, ManifestFormat::Toml).unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address: SocketAddr = listener.local_addr().unwrap();
+        let app = server::router(Arc::new(checker), sites, 4);
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        Self { base: format!("http://{}", address), client: reqwest::Client::new(), dir }
+    }
+
+    async fn start_scan(&self, request: Value) -> (u16, Value) {
+        let response = self.client.post(format!("{}/scans", self.base)).json(&request).send().await.unwrap();
+        (response.status().as_u16(), response.json().await.unwrap())
+    }
+
+    async fn get(&self, path: &str) -> (u16, Value) {
+        let response = self.client.get(format!("{}{}", self.base, path)).send().await.unwrap();
+        (response.status().as_u16(), response.json().await.unwrap())
+    }
+
+    async fn events(&self, id: u64) -> String {
+        let response = self.client.get(format!("{}/scans/{}/events", self.base, id)).send().await.unwrap();
+        response.text().await.unwrap()
+    }
+
+    /// Poll a scan's status until it finishes
+    async fn finished(&self, id: u64) -> Value {
+        for _ in 0..200 {
+            let (status, body) = self.get(&format!("/scans/{}", id)).await;
+            assert_eq!(status, 200, "{}", body);
+            if body["state"] == "finished" {
+                return body;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("scan {} never finished", id);
+    }
+}
+
+impl Drop for Api {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[tokio::test]
+async fn scans_report_their_results() {
+    let api = Api::start("status").await;
+    let (status, started) = api.start_scan(json!({ "username": "alice" })).await;
+    assert_eq!(status, 202);
+    let id = started["id"].as_u64().unwrap();
+    assert_eq!(started["status_url"], format!("/scans/{}", id));
+    assert_eq!(started["events_url"], format!("/scans/{}/events", id));
+
+    let scan = api.finished(id).await;
+    assert_eq!(scan["username"], "alice");
+    assert_eq!(scan["total"], 2);
+    assert_eq!(scan["completed"], 2);
+    assert_eq!(scan["found"], 1);
+    let mut results: Vec<(String, String)> = scan["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| (r["site"].as_str().unwrap().to_string(), r["result"]["status"].as_str().unwrap_or_default().to_string()))
+        .collect();
+    results.sort();
+    assert_eq!(results, [("Alpha".to_string(), "found".to_string()), ("Beta".to_string(), "not_found".to_string())]);
+}
+
+#[tokio::test]
+async fn events_stream_every_result_then_done() {
+    let api = Api::start("events").await;
+    let (_, started) = api.start_scan(json!({ "username": "alice", "sites": ["Beta"] })).await;
+    let id = started["id"].as_u64().unwrap();
+
+    // The stream ends after `done`, so the whole body can be read, whether or not the scan has
+    // finished by the time we subscribe
+    let body = api.events(id).await;
+    let events: Vec<(&str, Value)> = body
+        .split("\n\n")
+        .filter_map(|event| {
+            let name = event.lines().find_map(|line| line.strip_prefix("event: "))?;
+            let data = event.lines().find_map(|line| line.strip_prefix("data: "))?;
+            Some((name, serde_json::from_str(data).unwrap()))
+        })
+        .collect();
+    assert_eq!(events.len(), 2, "{}", body);
+    assert_eq!(events[0].0, "result");
+    assert_eq!(events[0].1["site"], "Beta");
+    assert_eq!(events[0].1["username"], "alice");
+    assert_eq!(events[1], ("done", json!({ "completed": 1 })));
+
+    // Late subscribers get the same replay
+    let again = api.events(id).await;
+    assert_eq!(again.matches("event: result").count(), 1);
+    assert!(again.contains("event: done"));
+}
+
+#[tokio::test]
+async fn requests_are_validated() {
+    let api = Api::start("validation").await;
+
+    let (status, body) = api.start_scan(json!({ "username": "alice", "sites": ["Alpha", "Gamma", "Delta"] })).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["error"], "unknown sites: Gamma, Delta");
+
+    let (status, body) = api.start_scan(json!({ "username": "  " })).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["error"], "username is empty");
+
+    let (status, body) = api.get("/scans/42").await;
+    assert_eq!(status, 404);
+    assert_eq!(body["error"], "no scan with id 42");
+    let (status, _) = api.get("/scans/42/events").await;
+    assert_eq!(status, 404);
+
+    let (status, sites) = api.get("/sites").await;
+    assert_eq!(status, 200);
+    let names: Vec<&str> = sites.as_array().unwrap().iter().map(|s| s["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["Alpha", "Beta"]);
+}
+
+#[tokio::test]
+async fn old_finished_scans_are_dropped() {
+    let api = Api::start("eviction").await;
+    let mut ids = Vec::new();
+    for _ in 0..KEPT_SCANS + 2 {
+        let (_, started) = api.start_scan(json!({ "username": "alice", "sites": ["Alpha"] })).await;
+        let id = started["id"].as_u64().unwrap();
+        api.finished(id).await;
+        ids.push(id);
+    }
+
+    for id in &ids[..2] {
+        let (status, _) = api.get(&format!("/scans/{}", id)).await;
+        assert_eq!(status, 404, "scan {} should have been dropped", id);
+    }
+    for id in &ids[2..] {
+        let (status, _) = api.get(&format!("/scans/{}", id)).await;
+        assert_eq!(status, 200, "scan {} should be kept", id);
+    }
+}